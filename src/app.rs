@@ -1,22 +1,55 @@
 use crate::bus::can;
+use crate::bus::can::coverage;
+use crate::bus::can::diagnostic::Diagnostic;
+use crate::bus::can::ecu::EcuRegistry;
+use crate::bus::compare;
+use crate::bus::database::Database;
+use crate::bus::repository::{self, Bus};
 use crate::config::Config;
-use log::debug;
+use log::{debug, warn};
 use std::fs;
+use std::sync::LazyLock;
 
 pub enum ActiveScreen {
     CanBus,
     Editing,
+    Problems,
+    Translations,
+    Compare,
+    Ecus,
 }
 
 pub enum EditWindow {
     NewCanMsg,
     EditCanMsg,
+    Translation,
 }
 
+pub const CONFIG_FILE: &str = "config.yaml";
+
 pub struct App {
     pub active_screen: ActiveScreen,
     pub edit_window: Option<EditWindow>,
-    pub can_messages: Vec<can::CanMessage>,
+    /// Buses found in `database_dir`, shown as tabs.
+    pub buses: Vec<Bus>,
+    /// Index in `buses` of the bus shown and used by commands.
+    pub active_bus: usize,
+    /// Index in `buses` of the bus compared with the active one.
+    pub compare_bus: usize,
+    /// Index in `comparisons()` of the message pair shown in the compare screen.
+    pub selected_comparison: usize,
+    /// Index in `ecu_registry().ecus()` of the ECU shown in the ECU screen.
+    pub selected_ecu: usize,
+    /// Index in the active database of the message shown in the details panel.
+    pub selected_message: usize,
+    /// Problems found while loading the buses, including files that failed to load.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Index in `missing_translations()` of the text selected for translation.
+    pub selected_missing: usize,
+    /// Text typed in the edit window.
+    pub input: String,
+    /// Result of the last action, shown in the footer.
+    pub status: Option<String>,
     pub app_config: Config,
 }
 
@@ -31,31 +64,294 @@ impl App {
         App {
             active_screen: ActiveScreen::CanBus,
             edit_window: None,
-            can_messages: Vec::new(),
-            app_config: Config::load_config_file("config.yaml"),
+            buses: Vec::new(),
+            active_bus: 0,
+            compare_bus: 0,
+            selected_comparison: 0,
+            selected_ecu: 0,
+            selected_message: 0,
+            diagnostics: Vec::new(),
+            selected_missing: 0,
+            input: String::new(),
+            status: None,
+            app_config: Config::load_config_file(CONFIG_FILE),
         }
     }
 
+    /// Loads every bus found in `database_dir`, replacing the current ones.
     pub fn load_can_messages(&mut self) {
-        for file_in_path in
-            fs::read_dir(self.app_config.database_dir.clone()).expect("Failed to read directory.")
-        {
-            let file_path = file_in_path.unwrap();
-            let f_path = file_path.path();
-
-            if f_path.is_file() {
-                if let Some(f_ext) = f_path.extension() {
-                    if f_ext == "yml" || f_ext == "yaml" {
-                        if let Some(file_path_str) = f_path.to_str() {
-                            debug!("PSA-RE-CLIENT opening file {}.", file_path_str);
-                            let can_message = can::CanMessage::from_yaml_file(&file_path_str)
-                                .expect("Failed to load CAN message.");
-                            self.can_messages.push(can_message);
-                        }
+        self.selected_message = 0;
+        self.selected_missing = 0;
+        let (ecus, ecu_diagnostics) = repository::load_ecus(&self.app_config.database_dir);
+        let (buses, diagnostics) = repository::load_buses(&self.app_config.database_dir, &ecus);
+        self.buses = buses;
+        self.diagnostics = diagnostics;
+        self.diagnostics.extend(ecu_diagnostics);
+        if self.active_bus >= self.buses.len() {
+            self.active_bus = 0;
+        }
+        // Files that failed to load first, then by file and line
+        self.diagnostics.sort_by(|a, b| {
+            b.is_load_failure()
+                .cmp(&a.is_load_failure())
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+    }
+
+    /// Database of the bus shown in the TUI and used by commands.
+    pub fn database(&self) -> &Database {
+        static EMPTY: LazyLock<Database> = LazyLock::new(Database::default);
+        self.buses
+            .get(self.active_bus)
+            .map_or(&EMPTY, |bus| &bus.database)
+    }
+
+    fn set_active_bus(&mut self, index: usize) {
+        self.active_bus = index;
+        self.selected_message = 0;
+        self.selected_missing = 0;
+        self.selected_ecu = 0;
+    }
+
+    /// ECUs of the active bus, with the messages they send and receive.
+    pub fn ecu_registry(&self) -> &EcuRegistry {
+        static EMPTY: LazyLock<EcuRegistry> = LazyLock::new(EcuRegistry::default);
+        self.buses
+            .get(self.active_bus)
+            .map_or(&EMPTY, |bus| &bus.ecus)
+    }
+
+    pub fn select_next_ecu(&mut self) {
+        if self.selected_ecu + 1 < self.ecu_registry().ecus().len() {
+            self.selected_ecu += 1;
+        }
+    }
+
+    pub fn select_previous_ecu(&mut self) {
+        self.selected_ecu = self.selected_ecu.saturating_sub(1);
+    }
+
+    pub fn select_next_bus(&mut self) {
+        if !self.buses.is_empty() {
+            self.set_active_bus((self.active_bus + 1) % self.buses.len());
+        }
+    }
+
+    pub fn select_previous_bus(&mut self) {
+        if !self.buses.is_empty() {
+            self.set_active_bus((self.active_bus + self.buses.len() - 1) % self.buses.len());
+        }
+    }
+
+    /// Index of the bus with this name, or network name if it is unique.
+    pub fn find_bus(&self, name: &str) -> Option<usize> {
+        self.buses
+            .iter()
+            .position(|bus| bus.name() == name)
+            .or_else(|| {
+                let mut matching = (0..self.buses.len()).filter(|&i| self.buses[i].network == name);
+                match (matching.next(), matching.next()) {
+                    (Some(index), None) => Some(index),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Makes the bus with this name the active one. Returns false if no such
+    /// bus is loaded.
+    pub fn select_bus(&mut self, name: &str) -> bool {
+        match self.find_bus(name) {
+            Some(index) => {
+                self.set_active_bus(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Opens the compare screen against the same network of another
+    /// architecture, or the next bus if there is none.
+    pub fn open_comparison(&mut self) {
+        if self.buses.len() < 2 {
+            self.status = Some(String::from("Comparing needs at least two buses."));
+            return;
+        }
+        let active = &self.buses[self.active_bus];
+        self.compare_bus = self
+            .buses
+            .iter()
+            .position(|bus| {
+                bus.network == active.network && bus.architecture != active.architecture
+            })
+            .unwrap_or((self.active_bus + 1) % self.buses.len());
+        self.selected_comparison = 0;
+        self.active_screen = ActiveScreen::Compare;
+    }
+
+    /// Compares with the next bus, skipping the active one.
+    pub fn select_next_compare_bus(&mut self) {
+        if self.buses.len() < 2 {
+            return;
+        }
+        self.compare_bus = (self.compare_bus + 1) % self.buses.len();
+        if self.compare_bus == self.active_bus {
+            self.compare_bus = (self.compare_bus + 1) % self.buses.len();
+        }
+        self.selected_comparison = 0;
+    }
+
+    /// Messages of the active bus matched with those of `compare_bus`.
+    pub fn comparisons(&self) -> Vec<compare::MessageComparison<'_>> {
+        match self.buses.get(self.compare_bus) {
+            Some(bus) => compare::compare(self.database(), &bus.database),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn select_next_comparison(&mut self) {
+        if self.selected_comparison + 1 < self.comparisons().len() {
+            self.selected_comparison += 1;
+        }
+    }
+
+    pub fn select_previous_comparison(&mut self) {
+        self.selected_comparison = self.selected_comparison.saturating_sub(1);
+    }
+
+    /// Number of files that could not be loaded at all.
+    pub fn failed_file_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_load_failure())
+            .count()
+    }
+
+    pub fn selected_can_message(&self) -> Option<&can::CanMessage> {
+        self.database().messages().get(self.selected_message)
+    }
+
+    pub fn select_next_message(&mut self) {
+        if self.selected_message + 1 < self.database().len() {
+            self.selected_message += 1;
+        }
+    }
+
+    pub fn select_previous_message(&mut self) {
+        self.selected_message = self.selected_message.saturating_sub(1);
+    }
+
+    /// Languages used by at least one text of the database, sorted.
+    pub fn available_languages(&self) -> Vec<String> {
+        let mut languages = vec![self.app_config.default_lang.clone()];
+        for message in self.database().messages() {
+            let translations = message
+                .comment
+                .iter()
+                .chain(message.signals.iter().flat_map(|(_, signal)| {
+                    signal
+                        .comment
+                        .iter()
+                        .chain(signal.values.iter().filter_map(|(_, label)| label.as_ref()))
+                }));
+            for translation in translations {
+                for lang in translation.languages() {
+                    if !languages.iter().any(|known| known == lang) {
+                        languages.push(lang.to_string());
                     }
                 }
             }
         }
-        self.can_messages.sort_by(|a, b| a.id.cmp(&b.id));
+        languages.sort();
+        languages
+    }
+
+    /// Switches the display language to the next available one and saves it
+    /// as `default_lang`.
+    pub fn cycle_language(&mut self) {
+        let languages = self.available_languages();
+        let current = languages
+            .iter()
+            .position(|lang| *lang == self.app_config.default_lang)
+            .unwrap_or(0);
+        self.app_config.default_lang = languages[(current + 1) % languages.len()].clone();
+        debug!("Display language set to {}.", self.app_config.default_lang);
+        if let Err(err) = self.app_config.save_config(CONFIG_FILE) {
+            warn!("Failed to save configuration: {}", err);
+            self.status = Some(format!("Failed to save configuration: {}", err));
+        }
+    }
+
+    /// Texts lacking a translation in the display language.
+    pub fn missing_translations(&self) -> Vec<coverage::MissingTranslation> {
+        let lang = &self.app_config.default_lang;
+        let reference_langs: Vec<&str> = self
+            .app_config
+            .language_chain()
+            .into_iter()
+            .filter(|l| l != lang)
+            .collect();
+        coverage::missing_translations(self.database().messages(), lang, &reference_langs)
+    }
+
+    pub fn select_next_missing(&mut self) {
+        if self.selected_missing + 1 < self.missing_translations().len() {
+            self.selected_missing += 1;
+        }
+    }
+
+    pub fn select_previous_missing(&mut self) {
+        self.selected_missing = self.selected_missing.saturating_sub(1);
+    }
+
+    /// Stores the typed text as translation of the selected missing text and
+    /// saves its message file.
+    pub fn save_translation(&mut self) {
+        let Some(missing) = self
+            .missing_translations()
+            .into_iter()
+            .nth(self.selected_missing)
+        else {
+            return;
+        };
+        let text = self.input.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+
+        let lang = self.app_config.default_lang.clone();
+        let Some(message) = self
+            .buses
+            .get_mut(self.active_bus)
+            .and_then(|bus| bus.database.message_mut(missing.message_index))
+        else {
+            return;
+        };
+        if !message.set_translation(&missing.location, &lang, &text) {
+            let problem = format!(
+                "{} no longer exists, translation not saved.",
+                missing.location.field_path()
+            );
+            warn!("{}", problem);
+            self.status = Some(problem);
+            self.input.clear();
+            return;
+        }
+        self.status = match message.save() {
+            Ok(()) => Some(format!(
+                "Saved {} in {}.",
+                missing.location.field_path(),
+                message.file_path.as_deref().unwrap_or("?")
+            )),
+            Err(err) => {
+                warn!("Failed to save translation: {}", err);
+                Some(format!("Failed to save: {}", err))
+            }
+        };
+        self.input.clear();
+        let remaining = self.missing_translations().len();
+        if self.selected_missing >= remaining {
+            self.selected_missing = remaining.saturating_sub(1);
+        }
     }
 }
@@ -1 +1,5 @@
 pub mod can;
+pub mod compare;
+pub mod database;
+pub mod format;
+pub mod repository;
@@ -1,18 +1,32 @@
-use log::{debug, warn};
+pub mod bits;
+pub mod coverage;
+pub mod decode;
+pub mod diagnostic;
+pub mod ecu;
+pub mod encode;
+pub mod id;
+pub mod lint;
+pub mod periodicity;
+pub mod writer;
+
+use bits::{BitRange, BitsError};
+use diagnostic::{Diagnostic, Diagnostics, Severity, field_path};
+use id::CanId;
+use log::debug;
+use periodicity::Periodicity;
 use std::fs;
 use yaml_rust2::{Yaml, YamlLoader};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Translation {
-    pub en: Option<String>,
-    pub fr: Option<String>,
-    pub de: Option<String>,
+    /// Texts by language code, in file order.
+    texts: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Signal {
     pub alt_names: Option<Vec<String>>,
-    pub bits: Option<String>,
+    pub bits: Option<Result<BitRange, BitsError>>,
     pub data_type: Option<String>,
     pub signed: Option<bool>,
     pub factor: Option<f64>,
@@ -23,43 +37,40 @@ pub struct Signal {
     pub comment: Option<Translation>,
     pub values: Vec<(i64, Option<Translation>)>,
     pub unused: Option<bool>,
+    /// Parameters this client does not know about, kept for writing back.
+    pub extra: Vec<(String, Yaml)>,
 }
 
 #[derive(Debug, Clone)]
 pub struct CanMessage {
-    pub id: Option<String>,
+    pub id: Option<CanId>,
     pub name: Option<String>,
     pub alt_names: Option<Vec<String>>,
     pub length: Option<i64>,
     pub comment: Option<Translation>,
     pub bus_type: Option<String>,
-    pub periodicity: Option<i64>,
+    pub periodicity: Option<Periodicity>,
     pub senders: Vec<String>,
     pub receivers: Vec<String>,
     pub signals: Vec<(String, Signal)>,
+    /// Parameters this client does not know about, kept for writing back.
+    pub extra: Vec<(String, Yaml)>,
+    /// YAML text the message was loaded from, used to keep comments and
+    /// formatting of untouched parts when saving.
+    pub source: Option<String>,
+    pub file_path: Option<String>,
 }
 
 impl Translation {
-    fn from_yaml(yaml: &Yaml) -> Option<Translation> {
+    fn from_yaml(yaml: &Yaml, diags: &mut Diagnostics, path: &str) -> Option<Translation> {
         if let Yaml::Hash(hash) = yaml {
-            let mut translation = Translation {
-                en: None,
-                fr: None,
-                de: None,
-            };
+            let mut translation = Translation { texts: Vec::new() };
 
             for (key, value) in hash {
                 if let (Yaml::String(k), Yaml::String(v)) = (key, value) {
-                    match k.as_str() {
-                        "en" => translation.en = Some(v.clone()),
-                        "fr" => translation.fr = Some(v.clone()),
-                        "de" => translation.de = Some(v.clone()),
-                        _ => {
-                            warn!("[WARNING] Unsupported language \"{}\".", k);
-                        }
-                    }
+                    translation.set(k, v);
                 } else {
-                    warn!("[WARNING] Wrong type for language translation.");
+                    diags.warning(path, "Wrong type for language translation.");
                 }
             }
 
@@ -68,10 +79,50 @@ impl Translation {
             None
         }
     }
+
+    /// Returns the text in the requested language, if present.
+    pub fn get(&self, lang: &str) -> Option<&str> {
+        self.texts
+            .iter()
+            .find(|(text_lang, _)| text_lang == lang)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// Sets the text of a language, replacing the previous one if any.
+    pub fn set(&mut self, lang: &str, text: &str) {
+        match self
+            .texts
+            .iter_mut()
+            .find(|(text_lang, _)| text_lang == lang)
+        {
+            Some((_, old_text)) => *old_text = text.to_string(),
+            None => self.texts.push((lang.to_string(), text.to_string())),
+        }
+    }
+
+    /// Iterates over the (language, text) pairs, in file order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.texts
+            .iter()
+            .map(|(lang, text)| (lang.as_str(), text.as_str()))
+    }
+
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.texts.iter().map(|(lang, _)| lang.as_str())
+    }
+
+    /// Returns the text in the first language of `langs` that is available,
+    /// or in any language when none of them is.
+    pub fn resolve(&self, langs: &[&str]) -> Option<&str> {
+        langs
+            .iter()
+            .find_map(|lang| self.get(lang))
+            .or_else(|| self.iter().next().map(|(_, text)| text))
+    }
 }
 
 impl Signal {
-    fn from_yaml(yaml: &Yaml) -> Signal {
+    fn from_yaml(yaml: &Yaml, diags: &mut Diagnostics, path: &str) -> Signal {
         let mut signal = Signal {
             alt_names: None,
             bits: None,
@@ -85,6 +136,7 @@ impl Signal {
             comment: None,
             values: Vec::new(),
             unused: None,
+            extra: Vec::new(),
         };
 
         if let Yaml::Hash(hash) = yaml {
@@ -103,28 +155,49 @@ impl Signal {
                                     signal.alt_names = Some(alt_names);
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"alt_names\".");
+                                diags.warning(
+                                    &field_path(path, "alt_names"),
+                                    "Wrong type for \"alt_names\".",
+                                );
                             }
                         }
                         "bits" => {
-                            if let Yaml::String(v) = value {
-                                signal.bits = Some(v.clone());
-                            } else {
-                                warn!("[WARNING] Wrong type for \"bits\".");
+                            // Single bits such as 3.5 are loaded as reals when left unquoted.
+                            match value {
+                                Yaml::String(v) | Yaml::Real(v) => {
+                                    let bits = BitRange::parse(v);
+                                    if let Err(err) = &bits {
+                                        diags.error(
+                                            &field_path(path, "bits"),
+                                            format!("Invalid \"bits\": {}.", err),
+                                        );
+                                    }
+                                    signal.bits = Some(bits);
+                                }
+                                _ => {
+                                    diags.warning(
+                                        &field_path(path, "bits"),
+                                        "Wrong type for \"bits\".",
+                                    );
+                                }
                             }
                         }
                         "type" => {
                             if let Yaml::String(v) = value {
                                 signal.data_type = Some(v.clone());
                             } else {
-                                warn!("[WARNING] Wrong type for \"type\".");
+                                diags
+                                    .warning(&field_path(path, "type"), "Wrong type for \"type\".");
                             }
                         }
                         "signed" => {
                             if let Yaml::Boolean(v) = value {
-                                signal.signed = Some(v.clone());
+                                signal.signed = Some(*v);
                             } else {
-                                warn!("[WARNING] Wrong type for \"signed\".");
+                                diags.warning(
+                                    &field_path(path, "signed"),
+                                    "Wrong type for \"signed\".",
+                                );
                             }
                         }
                         "factor" => {
@@ -132,7 +205,10 @@ impl Signal {
                                 Yaml::Real(v) => v.parse().ok(),
                                 Yaml::Integer(v) => Some(*v as f64),
                                 _ => {
-                                    warn!("[WARNING] Wrong type for \"factor\".");
+                                    diags.warning(
+                                        &field_path(path, "factor"),
+                                        "Wrong type for \"factor\".",
+                                    );
                                     None
                                 }
                             };
@@ -142,7 +218,10 @@ impl Signal {
                                 Yaml::Real(v) => v.parse().ok(),
                                 Yaml::Integer(v) => Some(*v as f64),
                                 _ => {
-                                    warn!("[WARNING] Wrong type for \"offset\".");
+                                    diags.warning(
+                                        &field_path(path, "offset"),
+                                        "Wrong type for \"offset\".",
+                                    );
                                     None
                                 }
                             };
@@ -152,7 +231,10 @@ impl Signal {
                                 Yaml::Real(v) => v.parse().ok(),
                                 Yaml::Integer(v) => Some(*v as f64),
                                 _ => {
-                                    warn!("[WARNING] Wrong type for \"min\".");
+                                    diags.warning(
+                                        &field_path(path, "min"),
+                                        "Wrong type for \"min\".",
+                                    );
                                     None
                                 }
                             };
@@ -162,7 +244,10 @@ impl Signal {
                                 Yaml::Real(v) => v.parse().ok(),
                                 Yaml::Integer(v) => Some(*v as f64),
                                 _ => {
-                                    warn!("[WARNING] Wrong type for \"max\".");
+                                    diags.warning(
+                                        &field_path(path, "max"),
+                                        "Wrong type for \"max\".",
+                                    );
                                     None
                                 }
                             };
@@ -171,35 +256,57 @@ impl Signal {
                             if let Yaml::String(v) = value {
                                 signal.units = Some(v.clone());
                             } else {
-                                warn!("[WARNING] Wrong type for \"units\".");
+                                diags.warning(
+                                    &field_path(path, "units"),
+                                    "Wrong type for \"units\".",
+                                );
                             }
                         }
                         "comment" => {
-                            signal.comment = Translation::from_yaml(value);
+                            signal.comment =
+                                Translation::from_yaml(value, diags, &field_path(path, "comment"));
                         }
                         "values" => {
                             if let Yaml::Hash(value_hash) = value {
                                 for (value_key, value_val) in value_hash {
                                     if let Yaml::Integer(value_num) = value_key {
-                                        let explanation = Translation::from_yaml(value_val);
-                                        signal.values.push((value_num.clone(), explanation));
+                                        let value_path = field_path(
+                                            &field_path(path, "values"),
+                                            &value_num.to_string(),
+                                        );
+                                        let explanation =
+                                            Translation::from_yaml(value_val, diags, &value_path);
+                                        signal.values.push((*value_num, explanation));
                                     } else {
-                                        warn!("[WARNING] Expected integer in field \"values\".");
+                                        diags.warning(
+                                            &field_path(path, "values"),
+                                            "Expected integer in field \"values\".",
+                                        );
                                     }
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"values\".");
+                                diags.warning(
+                                    &field_path(path, "values"),
+                                    "Wrong type for \"values\".",
+                                );
                             }
                         }
                         "unused" => {
                             if let Yaml::Boolean(v) = value {
-                                signal.unused = Some(v.clone());
+                                signal.unused = Some(*v);
                             } else {
-                                warn!("[WARNING] Wrong type for \"unused\".");
+                                diags.warning(
+                                    &field_path(path, "unused"),
+                                    "Wrong type for \"unused\".",
+                                );
                             }
                         }
                         _ => {
-                            warn!("[WARNING] Unknown CAN signal parameter \"{}\".", k);
+                            diags.warning(
+                                &field_path(path, k),
+                                format!("Unknown CAN signal parameter \"{}\".", k),
+                            );
+                            signal.extra.push((k.clone(), value.clone()));
                         }
                     }
                 }
@@ -210,9 +317,27 @@ impl Signal {
 }
 
 impl CanMessage {
-    fn from_yaml_str(yaml_str: &str) -> Result<CanMessage, Box<dyn std::error::Error>> {
-        let docs = YamlLoader::load_from_str(yaml_str)?;
-        let doc = &docs[0];
+    /// Parses a message, returning it along with the problems found. Fails only
+    /// when the text is not a YAML mapping.
+    pub fn from_yaml_str(
+        yaml_str: &str,
+        file_path: Option<&str>,
+    ) -> Result<(CanMessage, Vec<Diagnostic>), Diagnostic> {
+        let mut diags = Diagnostics::new(yaml_str, file_path);
+
+        let docs = YamlLoader::load_from_str(yaml_str).map_err(|err| {
+            let mut diagnostic = Diagnostic::new(Severity::Error, "", err.info());
+            diagnostic.line = Some(err.marker().line());
+            diagnostic.column = Some(err.marker().col() + 1);
+            diags.locate(diagnostic)
+        })?;
+        let Some(doc @ Yaml::Hash(_)) = docs.first() else {
+            return Err(diags.locate(Diagnostic::new(
+                Severity::Error,
+                "",
+                "Expected a mapping of CAN message parameters.",
+            )));
+        };
 
         let mut message = CanMessage {
             id: None,
@@ -225,6 +350,9 @@ impl CanMessage {
             senders: Vec::new(),
             receivers: Vec::new(),
             signals: Vec::new(),
+            extra: Vec::new(),
+            source: Some(yaml_str.to_string()),
+            file_path: None,
         };
 
         debug!("Loading CAN message header.");
@@ -234,16 +362,25 @@ impl CanMessage {
                     match k.as_str() {
                         "id" => {
                             message.id = match value {
-                                Yaml::String(v) => Some(v.clone()),
-                                Yaml::Integer(v) => Some(format!("0x{:X}", v)),
+                                Yaml::String(v) => CanId::parse(v),
+                                Yaml::Integer(v) => CanId::from_integer(*v),
                                 _ => None,
                             };
+                            if message.id.is_none() {
+                                let problem = if matches!(value, Yaml::String(_) | Yaml::Integer(_))
+                                {
+                                    "Invalid CAN identifier."
+                                } else {
+                                    "Wrong type for \"id\"."
+                                };
+                                diags.error("id", problem);
+                            }
                         }
                         "name" => {
                             if let Yaml::String(v) = value {
                                 message.name = Some(v.clone());
                             } else {
-                                warn!("[WARNING] Wrong type for \"name\".");
+                                diags.warning("name", "Wrong type for \"name\".");
                             }
                         }
                         "alt_names" => {
@@ -258,44 +395,42 @@ impl CanMessage {
                                     message.alt_names = Some(alt_names);
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"alt_names\".");
+                                diags.warning("alt_names", "Wrong type for \"alt_names\".");
                             }
                         }
                         "length" => {
                             if let Yaml::Integer(v) = value {
                                 message.length = Some(*v);
                             } else {
-                                warn!("[WARNING] Wrong type for \"length\".");
+                                diags.warning("length", "Wrong type for \"length\".");
                             }
                         }
                         "comment" => {
-                            message.comment = Translation::from_yaml(value);
+                            message.comment = Translation::from_yaml(value, &mut diags, "comment");
                         }
                         "type" => {
                             if let Yaml::String(v) = value {
                                 message.bus_type = Some(v.clone());
                             } else {
-                                warn!("[WARNING] Wrong type for \"type\".");
+                                diags.warning("type", "Wrong type for \"type\".");
                             }
                         }
                         "periodicity" => {
-                            if let Yaml::Integer(number) = value {
-                                message.periodicity = Some(number.clone());
-                            } else if let Yaml::String(text) = value {
-                                if text.eq("trigger") {
-                                    message.periodicity = Some(-1);
-                                } else if text.ends_with(" ms") {
-                                    let number: i64 = text.trim_end_matches(" ms").parse().unwrap();
-                                    message.periodicity = Some(number);
-                                } else if text.ends_with("ms") {
-                                    let number: i64 = text.trim_end_matches("ms").parse().unwrap();
-                                    message.periodicity = Some(number);
-                                } else {
-                                    warn!("[WARNING] Unable to parse \"periodicity\".");
+                            let periodicity = match value {
+                                Yaml::Integer(number) => Periodicity::from_integer(*number),
+                                Yaml::String(text) => Periodicity::parse(text),
+                                _ => {
+                                    diags.warning("periodicity", "Wrong type for \"periodicity\".");
+                                    continue;
                                 }
-                            } else {
-                                warn!("[WARNING] Wrong type for \"periodicity\".");
+                            };
+                            if let Periodicity::Unknown(text) = &periodicity {
+                                diags.warning(
+                                    "periodicity",
+                                    format!("Unable to parse \"periodicity\" \"{}\".", text),
+                                );
                             }
+                            message.periodicity = Some(periodicity);
                         }
                         "senders" => {
                             if let Yaml::Array(arr) = value {
@@ -305,7 +440,7 @@ impl CanMessage {
                                     }
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"senders\".");
+                                diags.warning("senders", "Wrong type for \"senders\".");
                             }
                         }
                         "receivers" => {
@@ -316,7 +451,7 @@ impl CanMessage {
                                     }
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"receivers\".");
+                                diags.warning("receivers", "Wrong type for \"receivers\".");
                             }
                         }
                         "signals" => {
@@ -324,26 +459,46 @@ impl CanMessage {
                                 for (signal_key, signal_value) in signals_hash {
                                     if let Yaml::String(signal_name) = signal_key {
                                         debug!("Loading CAN signal: {}.", signal_name);
-                                        let signal = Signal::from_yaml(signal_value);
+                                        let signal = Signal::from_yaml(
+                                            signal_value,
+                                            &mut diags,
+                                            &field_path("signals", signal_name),
+                                        );
                                         message.signals.push((signal_name.clone(), signal));
                                     }
                                 }
                             } else {
-                                warn!("[WARNING] Wrong type for \"signals\".");
+                                diags.warning("signals", "Wrong type for \"signals\".");
                             }
                         }
                         _ => {
-                            warn!("[WARNING] Unknown CAN message parameter \"{}\".", k);
+                            diags.warning(k, format!("Unknown CAN message parameter \"{}\".", k));
+                            message.extra.push((k.clone(), value.clone()));
                         }
                     }
                 }
             }
         }
-        Ok(message)
+        Ok((message, diags.list))
+    }
+
+    pub fn from_yaml_file(file_path: &str) -> Result<(CanMessage, Vec<Diagnostic>), Diagnostic> {
+        let yaml_content = fs::read_to_string(file_path).map_err(|err| {
+            let mut diagnostic = Diagnostic::new(Severity::Error, "", err.to_string());
+            diagnostic.file_path = Some(file_path.to_string());
+            diagnostic
+        })?;
+        let (mut message, diagnostics) = Self::from_yaml_str(&yaml_content, Some(file_path))?;
+        message.file_path = Some(file_path.to_string());
+        Ok((message, diagnostics))
     }
 
-    pub fn from_yaml_file(file_path: &str) -> Result<CanMessage, Box<dyn std::error::Error>> {
-        let yaml_content = fs::read_to_string(file_path)?;
-        Self::from_yaml_str(&yaml_content)
+    /// Id and name, e.g. "0x0F6 BSI_INFO_GEN", as shown in lists and graphs.
+    pub fn label(&self) -> String {
+        format!(
+            "{} {}",
+            self.id.map_or(String::from("?"), |id| id.to_string()),
+            self.name.as_deref().unwrap_or("")
+        )
     }
 }
@@ -0,0 +1,255 @@
+use std::fmt;
+
+/// Position of a single bit inside a CAN frame, in PSA-RE notation.
+///
+/// Bytes are numbered from 1, bits from 7 (MSB) down to 0 (LSB).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitPosition {
+    pub byte: u8,
+    pub bit: u8,
+}
+
+/// Contiguous range of bits, from the most significant bit (`start`) to the
+/// least significant one (`end`). Multi-byte ranges are big-endian, e.g.
+/// "2.7-3.0" spans the whole second and third bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitRange {
+    pub start: BitPosition,
+    pub end: BitPosition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitsError {
+    Empty,
+    InvalidPosition(String),
+    ByteOutOfRange(String),
+    BitOutOfRange(String),
+    ReversedRange(String),
+}
+
+impl fmt::Display for BitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitsError::Empty => write!(f, "empty bit range"),
+            BitsError::InvalidPosition(s) => {
+                write!(f, "invalid bits \"{}\", expected \"byte.bit\"", s)
+            }
+            BitsError::ByteOutOfRange(s) => write!(f, "byte number out of range in \"{}\"", s),
+            BitsError::BitOutOfRange(s) => write!(f, "bit number out of range in \"{}\"", s),
+            BitsError::ReversedRange(s) => {
+                write!(f, "bit range \"{}\" must go from MSB to LSB", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BitsError {}
+
+impl BitsError {
+    /// Original text that failed to parse.
+    pub fn text(&self) -> &str {
+        match self {
+            BitsError::Empty => "",
+            BitsError::InvalidPosition(s)
+            | BitsError::ByteOutOfRange(s)
+            | BitsError::BitOutOfRange(s)
+            | BitsError::ReversedRange(s) => s,
+        }
+    }
+}
+
+impl BitPosition {
+    /// Parses "byte.bit". Errors quote `range`, the whole text being parsed.
+    fn parse(text: &str, range: &str) -> Result<BitPosition, BitsError> {
+        let invalid = || BitsError::InvalidPosition(range.to_string());
+        let (byte_str, bit_str) = text.trim().split_once('.').ok_or_else(invalid)?;
+        let byte: u8 = byte_str.trim().parse().map_err(|_| invalid())?;
+        let bit: u8 = bit_str.trim().parse().map_err(|_| invalid())?;
+
+        if byte == 0 {
+            return Err(BitsError::ByteOutOfRange(range.to_string()));
+        }
+        if bit > 7 {
+            return Err(BitsError::BitOutOfRange(range.to_string()));
+        }
+        Ok(BitPosition { byte, bit })
+    }
+
+    /// Index of this bit when the frame is read MSB first, starting at 0 for
+    /// bit 7 of the first byte.
+    pub fn msb_index(&self) -> usize {
+        (self.byte as usize - 1) * 8 + (7 - self.bit as usize)
+    }
+}
+
+impl fmt::Display for BitPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.byte, self.bit)
+    }
+}
+
+impl BitRange {
+    /// Parses the PSA-RE notation: "byte.bit" for a single bit or
+    /// "byte.bit-byte.bit" for a range.
+    pub fn parse(text: &str) -> Result<BitRange, BitsError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(BitsError::Empty);
+        }
+
+        let range = match text.split_once('-') {
+            Some((start, end)) => BitRange {
+                start: BitPosition::parse(start, text)?,
+                end: BitPosition::parse(end, text)?,
+            },
+            None => {
+                let position = BitPosition::parse(text, text)?;
+                BitRange {
+                    start: position,
+                    end: position,
+                }
+            }
+        };
+
+        if range.start.msb_index() > range.end.msb_index() {
+            return Err(BitsError::ReversedRange(text.to_string()));
+        }
+        Ok(range)
+    }
+
+    /// Number of bits covered by the range.
+    pub fn width(&self) -> usize {
+        self.end.msb_index() - self.start.msb_index() + 1
+    }
+
+    /// Number of bytes needed to hold the range, i.e. the last byte used.
+    pub fn last_byte(&self) -> u8 {
+        self.end.byte
+    }
+
+    /// Iterates over every bit of the range, MSB first.
+    pub fn positions(&self) -> impl Iterator<Item = BitPosition> {
+        (self.start.msb_index()..=self.end.msb_index()).map(|index| BitPosition {
+            byte: (index / 8 + 1) as u8,
+            bit: (7 - index % 8) as u8,
+        })
+    }
+
+    /// Reads the raw value of the range from a payload, MSB first.
+    /// Returns `None` if the payload is too short or the range wider than 64 bits.
+    pub fn extract(&self, payload: &[u8]) -> Option<u64> {
+        if self.width() > 64 || payload.len() < self.last_byte() as usize {
+            return None;
+        }
+        let raw = self.positions().fold(0u64, |raw, position| {
+            let byte = payload[position.byte as usize - 1];
+            (raw << 1) | ((byte >> position.bit) & 1) as u64
+        });
+        Some(raw)
+    }
+
+    /// Writes a raw value into the range of a payload, MSB first. Bits of the
+    /// value above the range width are ignored.
+    /// Returns `None` if the payload is too short or the range wider than 64 bits.
+    pub fn insert(&self, payload: &mut [u8], raw: u64) -> Option<()> {
+        if self.width() > 64 || payload.len() < self.last_byte() as usize {
+            return None;
+        }
+        let width = self.width();
+        for (index, position) in self.positions().enumerate() {
+            let bit = (raw >> (width - 1 - index)) & 1;
+            let byte = &mut payload[position.byte as usize - 1];
+            *byte = (*byte & !(1 << position.bit)) | ((bit as u8) << position.bit);
+        }
+        Some(())
+    }
+
+    pub fn overlaps(&self, other: &BitRange) -> bool {
+        self.start.msb_index() <= other.end.msb_index()
+            && other.start.msb_index() <= self.end.msb_index()
+    }
+}
+
+impl fmt::Display for BitRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(byte: u8, bit: u8) -> BitPosition {
+        BitPosition { byte, bit }
+    }
+
+    #[test]
+    fn single_bit() {
+        let range = BitRange::parse("1.3").unwrap();
+        assert_eq!(range.start, position(1, 3));
+        assert_eq!(range.end, position(1, 3));
+        assert_eq!(range.width(), 1);
+        assert_eq!(range.to_string(), "1.3");
+    }
+
+    #[test]
+    fn whole_byte() {
+        let range = BitRange::parse("1.7-1.0").unwrap();
+        assert_eq!(range.start, position(1, 7));
+        assert_eq!(range.end, position(1, 0));
+        assert_eq!(range.width(), 8);
+        assert_eq!(range.to_string(), "1.7-1.0");
+    }
+
+    #[test]
+    fn multi_byte_is_big_endian() {
+        let range = BitRange::parse(" 2.7 - 3.0 ").unwrap();
+        assert_eq!(range.width(), 16);
+        assert_eq!(range.last_byte(), 3);
+        assert_eq!(range.extract(&[0x00, 0x12, 0x34]), Some(0x1234));
+
+        let mut payload = [0u8; 3];
+        range.insert(&mut payload, 0xABCD).unwrap();
+        assert_eq!(payload, [0x00, 0xAB, 0xCD]);
+    }
+
+    #[test]
+    fn reversed_range() {
+        assert_eq!(
+            BitRange::parse("1.0-1.7"),
+            Err(BitsError::ReversedRange(String::from("1.0-1.7")))
+        );
+    }
+
+    #[test]
+    fn byte_zero() {
+        assert_eq!(
+            BitRange::parse("0.7"),
+            Err(BitsError::ByteOutOfRange(String::from("0.7")))
+        );
+    }
+
+    #[test]
+    fn bit_above_seven() {
+        // Errors keep the whole range, so that it can be written back as is
+        assert_eq!(
+            BitRange::parse("1.7-1.9"),
+            Err(BitsError::BitOutOfRange(String::from("1.7-1.9")))
+        );
+    }
+
+    #[test]
+    fn empty_and_invalid() {
+        assert_eq!(BitRange::parse(""), Err(BitsError::Empty));
+        assert_eq!(BitRange::parse("  "), Err(BitsError::Empty));
+        assert_eq!(
+            BitRange::parse("1-2"),
+            Err(BitsError::InvalidPosition(String::from("1-2")))
+        );
+    }
+}
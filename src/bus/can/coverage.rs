@@ -0,0 +1,266 @@
+use super::diagnostic::field_path;
+use super::{CanMessage, Translation};
+
+/// Translatable text of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextLocation {
+    MessageComment,
+    SignalComment(String),
+    ValueLabel(String, i64),
+}
+
+/// Text that has no translation in the requested language.
+#[derive(Debug, Clone)]
+pub struct MissingTranslation {
+    /// Index of the message in the slice given to `missing_translations`.
+    pub message_index: usize,
+    pub location: TextLocation,
+    /// Text in another language, to translate from.
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileCoverage {
+    pub file_path: String,
+    pub total: usize,
+    pub translated: usize,
+}
+
+impl TextLocation {
+    /// Path of the text in the message file, e.g. `signals.SPEED.comment`.
+    pub fn field_path(&self) -> String {
+        match self {
+            TextLocation::MessageComment => String::from("comment"),
+            TextLocation::SignalComment(signal) => {
+                field_path(&field_path("signals", signal), "comment")
+            }
+            TextLocation::ValueLabel(signal, value) => field_path(
+                &field_path(&field_path("signals", signal), "values"),
+                &value.to_string(),
+            ),
+        }
+    }
+}
+
+impl FileCoverage {
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.translated as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl CanMessage {
+    /// Every translatable text of the message. Value labels are listed even
+    /// when they have no text at all.
+    pub fn translatable_texts(&self) -> Vec<(TextLocation, Option<&Translation>)> {
+        let mut texts = Vec::new();
+        if let Some(comment) = &self.comment {
+            texts.push((TextLocation::MessageComment, Some(comment)));
+        }
+        for (name, signal) in &self.signals {
+            if let Some(comment) = &signal.comment {
+                texts.push((TextLocation::SignalComment(name.clone()), Some(comment)));
+            }
+            for (value, label) in &signal.values {
+                texts.push((
+                    TextLocation::ValueLabel(name.clone(), *value),
+                    label.as_ref(),
+                ));
+            }
+        }
+        texts
+    }
+
+    /// Sets the text of a translatable text in one language.
+    /// Returns false if the location does not exist in this message.
+    pub fn set_translation(&mut self, location: &TextLocation, lang: &str, text: &str) -> bool {
+        let translation = match location {
+            TextLocation::MessageComment => self.comment.get_or_insert_with(Translation::default),
+            TextLocation::SignalComment(name) => {
+                let Some((_, signal)) = self.signals.iter_mut().find(|(n, _)| n == name) else {
+                    return false;
+                };
+                signal.comment.get_or_insert_with(Translation::default)
+            }
+            TextLocation::ValueLabel(name, value) => {
+                let Some((_, signal)) = self.signals.iter_mut().find(|(n, _)| n == name) else {
+                    return false;
+                };
+                let Some((_, label)) = signal.values.iter_mut().find(|(v, _)| v == value) else {
+                    return false;
+                };
+                label.get_or_insert_with(Translation::default)
+            }
+        };
+        translation.set(lang, text);
+        true
+    }
+}
+
+/// Lists the texts lacking a `lang` translation. `reference_langs` gives the
+/// languages to show the original text in.
+pub fn missing_translations(
+    messages: &[CanMessage],
+    lang: &str,
+    reference_langs: &[&str],
+) -> Vec<MissingTranslation> {
+    let mut missing = Vec::new();
+    for (message_index, message) in messages.iter().enumerate() {
+        for (location, translation) in message.translatable_texts() {
+            if translation.is_some_and(|t| t.get(lang).is_some()) {
+                continue;
+            }
+            missing.push(MissingTranslation {
+                message_index,
+                location,
+                reference: translation
+                    .and_then(|t| t.resolve(reference_langs))
+                    .map(String::from),
+            });
+        }
+    }
+    missing
+}
+
+/// Share of translatable texts having a `lang` translation, per file.
+pub fn coverage(messages: &[CanMessage], lang: &str) -> Vec<FileCoverage> {
+    let mut files: Vec<FileCoverage> = messages
+        .iter()
+        .map(|message| {
+            let texts = message.translatable_texts();
+            FileCoverage {
+                file_path: message.file_path.clone().unwrap_or_default(),
+                total: texts.len(),
+                translated: texts
+                    .iter()
+                    .filter(|(_, translation)| translation.is_some_and(|t| t.get(lang).is_some()))
+                    .count(),
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<CanMessage> {
+        [
+            (
+                "b/0F6.yml",
+                "id: 0x0F6
+comment:
+  en: \"General info\"
+  fr: \"Infos générales\"
+signals:
+  SPEED:
+    bits: \"1.7-2.0\"
+    comment:
+      en: \"Speed\"
+  TURN:
+    bits: \"3.1-3.0\"
+    values:
+      0:
+        fr: \"arrêt\"
+      1:
+",
+            ),
+            ("a/036.yml", "id: 0x036\n"),
+        ]
+        .iter()
+        .map(|(file_path, text)| {
+            let mut message = CanMessage::from_yaml_str(text, None).unwrap().0;
+            message.file_path = Some(file_path.to_string());
+            message
+        })
+        .collect()
+    }
+
+    #[test]
+    fn missing_translations_with_reference() {
+        let missing = missing_translations(&messages(), "fr", &["de", "en"]);
+        let found: Vec<(usize, String, Option<&str>)> = missing
+            .iter()
+            .map(|missing| {
+                (
+                    missing.message_index,
+                    missing.location.field_path(),
+                    missing.reference.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0, String::from("signals.SPEED.comment"), Some("Speed")),
+                (0, String::from("signals.TURN.values.1"), None),
+            ]
+        );
+
+        let missing = missing_translations(&messages(), "en", &["fr"]);
+        assert_eq!(
+            missing[0].location,
+            TextLocation::ValueLabel(String::from("TURN"), 0)
+        );
+        assert_eq!(missing[0].reference.as_deref(), Some("arrêt"));
+        assert_eq!(missing.len(), 2);
+    }
+
+    #[test]
+    fn coverage_per_file() {
+        let coverage = coverage(&messages(), "fr");
+        let found: Vec<(&str, usize, usize, f64)> = coverage
+            .iter()
+            .map(|file| {
+                (
+                    file.file_path.as_str(),
+                    file.total,
+                    file.translated,
+                    file.percent(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [("a/036.yml", 0, 0, 100.0), ("b/0F6.yml", 4, 2, 50.0)]
+        );
+    }
+
+    #[test]
+    fn set_translation_fills_missing_texts() {
+        let mut messages = messages();
+        let message = &mut messages[0];
+        assert!(message.set_translation(
+            &TextLocation::SignalComment(String::from("SPEED")),
+            "fr",
+            "Vitesse"
+        ));
+        assert!(message.set_translation(
+            &TextLocation::ValueLabel(String::from("TURN"), 1),
+            "fr",
+            "marche"
+        ));
+        assert!(missing_translations(&messages, "fr", &["en"]).is_empty());
+    }
+
+    #[test]
+    fn set_translation_of_missing_location() {
+        let mut message = messages().remove(0);
+        assert!(!message.set_translation(
+            &TextLocation::SignalComment(String::from("RPM")),
+            "fr",
+            "Régime"
+        ));
+        assert!(!message.set_translation(
+            &TextLocation::ValueLabel(String::from("TURN"), 7),
+            "fr",
+            "?"
+        ));
+        assert_eq!(message.translatable_texts().len(), 4);
+    }
+}
@@ -0,0 +1,176 @@
+use super::bits::BitsError;
+use super::{CanMessage, Signal};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct DecodedSignal {
+    pub name: String,
+    pub raw: u64,
+    pub value: f64,
+    pub units: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DecodeError {
+    MissingBits(String),
+    InvalidBits(String, BitsError),
+    PayloadTooShort(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::MissingBits(name) => write!(f, "signal \"{}\" has no bits", name),
+            DecodeError::InvalidBits(name, err) => {
+                write!(f, "signal \"{}\" has invalid bits: {}", name, err)
+            }
+            DecodeError::PayloadTooShort(name) => {
+                write!(f, "payload too short for signal \"{}\"", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Signal {
+    pub fn is_signed(&self) -> bool {
+        self.signed.unwrap_or(false)
+    }
+
+    /// Interprets a raw value as an integer, sign extending it for signed signals.
+    pub fn raw_to_integer(&self, raw: u64, width: usize) -> i64 {
+        if self.is_signed() && width < 64 && raw >> (width - 1) & 1 == 1 {
+            (raw as i64) - (1i64 << width)
+        } else {
+            raw as i64
+        }
+    }
+
+    /// Applies `factor` and `offset` to an integer value.
+    pub fn integer_to_physical(&self, integer: i64) -> f64 {
+        integer as f64 * self.factor.unwrap_or(1.0) + self.offset.unwrap_or(0.0)
+    }
+
+    /// Returns the label of an enum value, in the first available language of `langs`.
+    pub fn value_label(&self, integer: i64, langs: &[&str]) -> Option<String> {
+        let (_, translation) = self.values.iter().find(|(value, _)| *value == integer)?;
+        translation.as_ref()?.resolve(langs).map(String::from)
+    }
+
+    pub fn decode(
+        &self,
+        name: &str,
+        payload: &[u8],
+        langs: &[&str],
+    ) -> Result<DecodedSignal, DecodeError> {
+        let range = match &self.bits {
+            Some(Ok(range)) => range,
+            Some(Err(err)) => return Err(DecodeError::InvalidBits(name.to_string(), err.clone())),
+            None => return Err(DecodeError::MissingBits(name.to_string())),
+        };
+        let raw = range
+            .extract(payload)
+            .ok_or_else(|| DecodeError::PayloadTooShort(name.to_string()))?;
+        let integer = self.raw_to_integer(raw, range.width());
+
+        Ok(DecodedSignal {
+            name: name.to_string(),
+            raw,
+            value: self.integer_to_physical(integer),
+            units: self.units.clone(),
+            label: self.value_label(integer, langs),
+        })
+    }
+}
+
+impl CanMessage {
+    /// Decodes every signal of the message from a raw payload.
+    pub fn decode(
+        &self,
+        payload: &[u8],
+        langs: &[&str],
+    ) -> Vec<Result<DecodedSignal, DecodeError>> {
+        self.signals
+            .iter()
+            .map(|(name, signal)| signal.decode(name, payload, langs))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "id: 0x0F6
+length: 8
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    factor: 0.5
+    offset: -40
+    units: \"°C\"
+  ACCEL:
+    bits: \"3.7-4.0\"
+    signed: true
+  GEAR:
+    bits: \"5.3-5.0\"
+    type: enum
+    values:
+      0:
+        en: \"park\"
+        fr: \"parking\"
+      1:
+        en: \"reverse\"
+";
+
+    fn message() -> CanMessage {
+        CanMessage::from_yaml_str(MESSAGE, None).unwrap().0
+    }
+
+    fn decode(name: &str, payload: &[u8]) -> Result<DecodedSignal, DecodeError> {
+        let message = message();
+        let (_, signal) = message.signals.iter().find(|(n, _)| n == name).unwrap();
+        signal.decode(name, payload, &["fr", "en"])
+    }
+
+    #[test]
+    fn factor_and_offset() {
+        let decoded = decode("TEMP", &[0, 130, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.raw, 130);
+        assert_eq!(decoded.value, 25.0);
+        assert_eq!(decoded.units.as_deref(), Some("°C"));
+    }
+
+    #[test]
+    fn signed_values_are_sign_extended() {
+        let decoded = decode("ACCEL", &[0, 0, 0xFF, 0xFE, 0, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.raw, 0xFFFE);
+        assert_eq!(decoded.value, -2.0);
+
+        let decoded = decode("ACCEL", &[0, 0, 0x7F, 0xFF, 0, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.value, 32767.0);
+    }
+
+    #[test]
+    fn labels_follow_the_language_chain() {
+        let decoded = decode("GEAR", &[0, 0, 0, 0, 0xF0, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.value, 0.0);
+        assert_eq!(decoded.label.as_deref(), Some("parking"));
+
+        let decoded = decode("GEAR", &[0, 0, 0, 0, 0x01, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.label.as_deref(), Some("reverse"));
+
+        let decoded = decode("GEAR", &[0, 0, 0, 0, 0x02, 0, 0, 0]).unwrap();
+        assert_eq!(decoded.label, None);
+    }
+
+    #[test]
+    fn short_payload() {
+        assert!(matches!(
+            decode("ACCEL", &[0, 0, 0]),
+            Err(DecodeError::PayloadTooShort(_))
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+use log::warn;
+use std::collections::HashMap;
+use std::fmt;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem found while reading a database file.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file_path: Option<String>,
+    /// Line and column of the field in the YAML file, both starting at 1.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Dotted path of the field, e.g. `signals.SPEED.factor`. Empty for the whole file.
+    pub field: String,
+    pub message: String,
+    /// Set when the problem is about a whole directory rather than one file.
+    pub directory: bool,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file_path) = &self.file_path {
+            write!(f, "{}:", file_path)?;
+        }
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}:", line, column)?,
+            (Some(line), None) => write!(f, "{}:", line)?,
+            _ => {}
+        }
+        if self.file_path.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}: ", self.severity)?;
+        if !self.field.is_empty() {
+            write!(f, "{}: ", self.field)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, field: &str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file_path: None,
+            line: None,
+            column: None,
+            field: field.to_string(),
+            message: message.into(),
+            directory: false,
+        }
+    }
+
+    /// Error about a directory that could not be read or holds no messages.
+    pub fn directory(dir: &str, message: impl Into<String>) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(Severity::Error, "", message);
+        diagnostic.file_path = Some(dir.to_string());
+        diagnostic.directory = true;
+        diagnostic
+    }
+
+    /// True for errors about a whole file, which prevented loading it.
+    pub fn is_load_failure(&self) -> bool {
+        self.severity == Severity::Error && self.field.is_empty() && !self.directory
+    }
+}
+
+/// Joins a field path and a key, e.g. `signals.SPEED` and `factor`.
+pub fn field_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+enum Frame {
+    Mapping { path: String, key: Option<String> },
+    Sequence { path: String, index: usize },
+}
+
+/// Position of every mapping key of a YAML document, by field path.
+#[derive(Default)]
+pub struct KeyPositions {
+    positions: HashMap<String, (usize, usize)>,
+    stack: Vec<Frame>,
+}
+
+impl KeyPositions {
+    pub fn parse(source: &str) -> KeyPositions {
+        let mut positions = KeyPositions::default();
+        // Syntax errors are reported by the loader, positions found so far are kept.
+        let _ = Parser::new_from_str(source).load(&mut positions, false);
+        positions
+    }
+
+    pub fn get(&self, field: &str) -> Option<(usize, usize)> {
+        self.positions.get(field).copied()
+    }
+
+    /// Path of the node starting now, and marks the parent as done with it.
+    fn next_child_path(&mut self) -> Option<String> {
+        match self.stack.last_mut()? {
+            Frame::Mapping { path, key } => key.take().map(|k| field_path(path, &k)),
+            Frame::Sequence { path, index } => {
+                *index += 1;
+                Some(format!("{}[{}]", path, *index - 1))
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for KeyPositions {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(text, ..) => {
+                if let Some(Frame::Mapping { path, key }) = self.stack.last_mut()
+                    && key.is_none()
+                {
+                    let field = field_path(path, &text);
+                    self.positions.insert(field, (mark.line(), mark.col() + 1));
+                    *key = Some(text);
+                } else {
+                    self.next_child_path();
+                }
+            }
+            Event::Alias(_) => {
+                self.next_child_path();
+            }
+            Event::MappingStart(..) => {
+                let path = self.next_child_path().unwrap_or_default();
+                self.stack.push(Frame::Mapping { path, key: None });
+            }
+            Event::SequenceStart(..) => {
+                let path = self.next_child_path().unwrap_or_default();
+                self.stack.push(Frame::Sequence { path, index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects the diagnostics of one file while it is being parsed.
+pub struct Diagnostics {
+    file_path: Option<String>,
+    positions: KeyPositions,
+    pub list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(source: &str, file_path: Option<&str>) -> Diagnostics {
+        Diagnostics {
+            file_path: file_path.map(String::from),
+            positions: KeyPositions::parse(source),
+            list: Vec::new(),
+        }
+    }
+
+    /// Fills in the file and position of a diagnostic.
+    pub fn locate(&self, mut diagnostic: Diagnostic) -> Diagnostic {
+        if diagnostic.file_path.is_none() {
+            diagnostic.file_path = self.file_path.clone();
+        }
+        if diagnostic.line.is_none()
+            && let Some((line, column)) = self.positions.get(&diagnostic.field)
+        {
+            diagnostic.line = Some(line);
+            diagnostic.column = Some(column);
+        }
+        diagnostic
+    }
+
+    pub fn push(&mut self, severity: Severity, field: &str, message: impl Into<String>) {
+        let diagnostic = self.locate(Diagnostic::new(severity, field, message));
+        warn!("{}", diagnostic);
+        self.list.push(diagnostic);
+    }
+
+    pub fn warning(&mut self, field: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, field, message);
+    }
+
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.push(Severity::Error, field, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::can::CanMessage;
+
+    const SOURCE: &str = "id: 0x0F6
+name: BSI_INFO
+senders: [BSI, CMB]
+signals:
+  SPEED:
+    bits: \"1.7-2.0\"
+    values:
+      0:
+        en: \"stopped\"
+";
+
+    #[test]
+    fn key_positions() {
+        let positions = KeyPositions::parse(SOURCE);
+        assert_eq!(positions.get("id"), Some((1, 1)));
+        assert_eq!(positions.get("signals"), Some((4, 1)));
+        assert_eq!(positions.get("signals.SPEED"), Some((5, 3)));
+        assert_eq!(positions.get("signals.SPEED.bits"), Some((6, 5)));
+        assert_eq!(positions.get("signals.SPEED.values.0.en"), Some((9, 9)));
+        assert_eq!(positions.get("senders"), Some((3, 1)));
+        assert_eq!(positions.get("signals.SPEED.factor"), None);
+    }
+
+    #[test]
+    fn diagnostics_are_located() {
+        let source = SOURCE.replace("bits: \"1.7-2.0\"", "bits: [1]");
+        let (_, diagnostics) = CanMessage::from_yaml_str(&source, Some("0F6.yml")).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.field, "signals.SPEED.bits");
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(6), Some(5)));
+        assert!(
+            diagnostic
+                .to_string()
+                .starts_with("0F6.yml:6:5: warning: signals.SPEED.bits: ")
+        );
+    }
+
+    #[test]
+    fn wrong_id_type_is_reported_once() {
+        let (message, diagnostics) =
+            CanMessage::from_yaml_str("name: BSI_INFO\nid: [0x0F6]\n", None).unwrap();
+        assert_eq!(message.id, None);
+        let found: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(found, ["2:1: error: id: Wrong type for \"id\"."]);
+
+        let (_, diagnostics) = CanMessage::from_yaml_str("id: 0xFFFFFFFFF\n", None).unwrap();
+        let found: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(found, ["1:1: error: id: Invalid CAN identifier."]);
+    }
+}
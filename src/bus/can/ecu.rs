@@ -0,0 +1,292 @@
+use super::diagnostic::{Diagnostic, Diagnostics, Severity};
+use super::{CanMessage, Translation};
+use log::debug;
+use std::collections::HashMap;
+use std::fs;
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// Electronic control unit, from a description file or named as sender or
+/// receiver of a message.
+#[derive(Debug, Clone, Default)]
+pub struct Ecu {
+    pub name: String,
+    pub alt_names: Vec<String>,
+    pub comment: Option<Translation>,
+    /// Description file, if the ECU has one.
+    pub file_path: Option<String>,
+    /// Indexes of the messages the ECU sends.
+    pub transmits: Vec<usize>,
+    /// Indexes of the messages the ECU receives.
+    pub receives: Vec<usize>,
+}
+
+/// ECUs of a bus, indexed by name and alternative name, and by the messages
+/// they send and receive.
+#[derive(Debug, Clone, Default)]
+pub struct EcuRegistry {
+    ecus: Vec<Ecu>,
+    by_name: HashMap<String, usize>,
+    /// Indexes in `ecus` of the senders of each message.
+    producers: Vec<Vec<usize>>,
+    /// Indexes in `ecus` of the receivers of each message.
+    consumers: Vec<Vec<usize>>,
+}
+
+impl Ecu {
+    fn from_yaml_str(
+        yaml_str: &str,
+        file_path: Option<&str>,
+    ) -> Result<(Ecu, Vec<Diagnostic>), Diagnostic> {
+        let mut diags = Diagnostics::new(yaml_str, file_path);
+
+        let docs = YamlLoader::load_from_str(yaml_str).map_err(|err| {
+            let mut diagnostic = Diagnostic::new(Severity::Error, "", err.info());
+            diagnostic.line = Some(err.marker().line());
+            diagnostic.column = Some(err.marker().col() + 1);
+            diags.locate(diagnostic)
+        })?;
+        let Some(Yaml::Hash(hash)) = docs.first() else {
+            return Err(diags.locate(Diagnostic::new(
+                Severity::Error,
+                "",
+                "Expected a mapping of ECU parameters.",
+            )));
+        };
+
+        let mut ecu = Ecu::default();
+        for (key, value) in hash {
+            if let Yaml::String(k) = key {
+                match k.as_str() {
+                    "name" => {
+                        if let Yaml::String(v) = value {
+                            ecu.name = v.clone();
+                        } else {
+                            diags.warning("name", "Wrong type for \"name\".");
+                        }
+                    }
+                    "alt_names" => {
+                        if let Yaml::Array(arr) = value {
+                            ecu.alt_names = arr
+                                .iter()
+                                .filter_map(|item| item.as_str().map(String::from))
+                                .collect();
+                        } else {
+                            diags.warning("alt_names", "Wrong type for \"alt_names\".");
+                        }
+                    }
+                    "comment" => {
+                        ecu.comment = Translation::from_yaml(value, &mut diags, "comment");
+                    }
+                    _ => {
+                        diags.warning(k, format!("Unknown ECU parameter \"{}\".", k));
+                    }
+                }
+            }
+        }
+
+        if ecu.name.is_empty() {
+            return Err(diags.locate(Diagnostic::new(
+                Severity::Error,
+                "name",
+                "ECU description has no name.",
+            )));
+        }
+        Ok((ecu, diags.list))
+    }
+
+    /// Loads an ECU description file, with `name`, `alt_names` and `comment`.
+    pub fn from_yaml_file(file_path: &str) -> Result<(Ecu, Vec<Diagnostic>), Diagnostic> {
+        let yaml_content = fs::read_to_string(file_path).map_err(|err| {
+            let mut diagnostic = Diagnostic::new(Severity::Error, "", err.to_string());
+            diagnostic.file_path = Some(file_path.to_string());
+            diagnostic
+        })?;
+        let (mut ecu, diagnostics) = Self::from_yaml_str(&yaml_content, Some(file_path))?;
+        ecu.file_path = Some(file_path.to_string());
+        Ok((ecu, diagnostics))
+    }
+}
+
+impl EcuRegistry {
+    /// Builds the registry from the ECU descriptions and the senders and
+    /// receivers of `messages`. Names not described get an ECU of their own,
+    /// and described ECUs that neither send nor receive on the bus are left out.
+    pub fn new(messages: &[CanMessage], descriptions: &[Ecu]) -> EcuRegistry {
+        let mut registry = EcuRegistry::default();
+        for description in descriptions {
+            registry.ecus.push(Ecu {
+                transmits: Vec::new(),
+                receives: Vec::new(),
+                ..description.clone()
+            });
+        }
+        registry.reindex();
+
+        for (message_index, message) in messages.iter().enumerate() {
+            for name in &message.senders {
+                let index = registry.index_or_insert(name);
+                let transmits = &mut registry.ecus[index].transmits;
+                if !transmits.contains(&message_index) {
+                    transmits.push(message_index);
+                }
+            }
+            for name in &message.receivers {
+                let index = registry.index_or_insert(name);
+                let receives = &mut registry.ecus[index].receives;
+                if !receives.contains(&message_index) {
+                    receives.push(message_index);
+                }
+            }
+        }
+
+        registry
+            .ecus
+            .retain(|ecu| !ecu.transmits.is_empty() || !ecu.receives.is_empty());
+        registry.ecus.sort_by(|a, b| a.name.cmp(&b.name));
+        registry.reindex();
+
+        registry.producers = vec![Vec::new(); messages.len()];
+        registry.consumers = vec![Vec::new(); messages.len()];
+        for (ecu_index, ecu) in registry.ecus.iter().enumerate() {
+            for &message_index in &ecu.transmits {
+                registry.producers[message_index].push(ecu_index);
+            }
+            for &message_index in &ecu.receives {
+                registry.consumers[message_index].push(ecu_index);
+            }
+        }
+        registry
+    }
+
+    fn reindex(&mut self) {
+        self.by_name.clear();
+        for (index, ecu) in self.ecus.iter().enumerate() {
+            for name in std::iter::once(&ecu.name).chain(&ecu.alt_names) {
+                self.by_name.entry(name.clone()).or_insert(index);
+            }
+        }
+    }
+
+    fn index_or_insert(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.by_name.get(name) {
+            return index;
+        }
+        debug!("ECU {} has no description.", name);
+        self.ecus.push(Ecu {
+            name: name.to_string(),
+            ..Ecu::default()
+        });
+        self.by_name.insert(name.to_string(), self.ecus.len() - 1);
+        self.ecus.len() - 1
+    }
+
+    pub fn ecus(&self) -> &[Ecu] {
+        &self.ecus
+    }
+
+    /// ECU with this name or alternative name.
+    pub fn get(&self, name: &str) -> Option<&Ecu> {
+        self.by_name.get(name).map(|&index| &self.ecus[index])
+    }
+
+    /// ECUs sending the message at `message_index`.
+    pub fn producers(&self, message_index: usize) -> Vec<&Ecu> {
+        self.producers
+            .get(message_index)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.ecus[index])
+            .collect()
+    }
+
+    /// ECUs receiving the message at `message_index`.
+    pub fn consumers(&self, message_index: usize) -> Vec<&Ecu> {
+        self.consumers
+            .get(message_index)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.ecus[index])
+            .collect()
+    }
+}
+
+/// Indexes of the messages that no ECU sends.
+pub fn unsent_messages(messages: &[CanMessage]) -> Vec<usize> {
+    messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.senders.is_empty())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<CanMessage> {
+        [
+            "id: 0x036\nname: COMMANDES_BSI\nsenders: [BSI]\nreceivers: [CMB, ABS]\n",
+            "id: 0x0F6\nname: BSI_INFO\nsenders: [BSI, BSM]\nreceivers: [CMB]\n",
+            "id: 0x128\nname: CMB_INFO\nreceivers: [BSI]\n",
+        ]
+        .iter()
+        .map(|text| CanMessage::from_yaml_str(text, None).unwrap().0)
+        .collect()
+    }
+
+    fn description(text: &str) -> Ecu {
+        Ecu::from_yaml_str(text, None).unwrap().0
+    }
+
+    fn names(ecus: Vec<&Ecu>) -> Vec<&str> {
+        ecus.iter().map(|ecu| ecu.name.as_str()).collect()
+    }
+
+    #[test]
+    fn producers_and_consumers() {
+        let registry = EcuRegistry::new(&messages(), &[]);
+        assert_eq!(names(registry.producers(0)), ["BSI"]);
+        assert_eq!(names(registry.consumers(0)), ["ABS", "CMB"]);
+        assert_eq!(names(registry.producers(1)), ["BSI", "BSM"]);
+        assert_eq!(names(registry.consumers(1)), ["CMB"]);
+        assert!(registry.producers(2).is_empty());
+        assert_eq!(names(registry.consumers(2)), ["BSI"]);
+        assert!(registry.producers(3).is_empty());
+
+        let bsi = registry.get("BSI").unwrap();
+        assert_eq!(bsi.transmits, [0, 1]);
+        assert_eq!(bsi.receives, [2]);
+    }
+
+    #[test]
+    fn descriptions_are_merged_by_alt_name() {
+        let descriptions = [description(
+            "name: BSI\nalt_names: [BSM]\ncomment:\n  en: \"Body controller\"\n",
+        )];
+        let registry = EcuRegistry::new(&messages(), &descriptions);
+        let bsi = registry.get("BSM").unwrap();
+        assert_eq!(bsi.name, "BSI");
+        assert_eq!(bsi.transmits, [0, 1]);
+        assert!(bsi.comment.is_some());
+        assert_eq!(names(registry.producers(1)), ["BSI"]);
+    }
+
+    #[test]
+    fn only_ecus_with_traffic_are_listed() {
+        let descriptions = [description("name: BSI\n"), description("name: ESP\n")];
+        let registry = EcuRegistry::new(&messages(), &descriptions);
+        let listed: Vec<&str> = registry
+            .ecus()
+            .iter()
+            .map(|ecu| ecu.name.as_str())
+            .collect();
+        assert_eq!(listed, ["ABS", "BSI", "BSM", "CMB"]);
+        assert!(registry.get("ESP").is_none());
+    }
+
+    #[test]
+    fn messages_without_sender() {
+        assert_eq!(unsent_messages(&messages()), [2]);
+    }
+}
@@ -0,0 +1,280 @@
+use super::bits::BitsError;
+use super::{CanMessage, Signal};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Value given for a signal when building a frame.
+#[derive(Debug, Clone)]
+pub enum SignalValue {
+    Physical(f64),
+    /// Label of one of the signal `values`, in any language.
+    Label(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum EncodeError {
+    MissingLength,
+    UnknownSignal(String),
+    UnknownLabel(String, String),
+    MissingBits(String),
+    InvalidBits(String, BitsError),
+    BelowMin(String, f64, f64),
+    AboveMax(String, f64, f64),
+    NotEncodable(String, f64),
+    BeyondLength(String),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::MissingLength => write!(f, "message has no length"),
+            EncodeError::UnknownSignal(name) => write!(f, "unknown signal \"{}\"", name),
+            EncodeError::UnknownLabel(name, label) => {
+                write!(f, "signal \"{}\" has no value \"{}\"", name, label)
+            }
+            EncodeError::MissingBits(name) => write!(f, "signal \"{}\" has no bits", name),
+            EncodeError::InvalidBits(name, err) => {
+                write!(f, "signal \"{}\" has invalid bits: {}", name, err)
+            }
+            EncodeError::BelowMin(name, value, min) => {
+                write!(
+                    f,
+                    "value {} of signal \"{}\" is below min {}",
+                    value, name, min
+                )
+            }
+            EncodeError::AboveMax(name, value, max) => {
+                write!(
+                    f,
+                    "value {} of signal \"{}\" is above max {}",
+                    value, name, max
+                )
+            }
+            EncodeError::NotEncodable(name, value) => {
+                write!(f, "value {} does not fit in signal \"{}\"", value, name)
+            }
+            EncodeError::BeyondLength(name) => {
+                write!(f, "signal \"{}\" goes beyond the message length", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl Signal {
+    /// Returns the integer matching an enum label, in any language.
+    pub fn label_to_integer(&self, label: &str) -> Option<i64> {
+        self.values
+            .iter()
+            .find(|(_, translation)| {
+                translation
+                    .as_ref()
+                    .is_some_and(|t| t.iter().any(|(_, text)| text == label))
+            })
+            .map(|(value, _)| *value)
+    }
+
+    /// Range of integers that fit in a signal of the given width.
+    pub fn integer_range(&self, width: usize) -> (i64, i64) {
+        if width >= 64 {
+            (i64::MIN, i64::MAX)
+        } else if self.is_signed() {
+            (-(1i64 << (width - 1)), (1i64 << (width - 1)) - 1)
+        } else {
+            (0, (1i64 << width) - 1)
+        }
+    }
+
+    pub fn encode(
+        &self,
+        name: &str,
+        value: &SignalValue,
+        payload: &mut [u8],
+    ) -> Result<(), EncodeError> {
+        let range = match &self.bits {
+            Some(Ok(range)) => range,
+            Some(Err(err)) => return Err(EncodeError::InvalidBits(name.to_string(), err.clone())),
+            None => return Err(EncodeError::MissingBits(name.to_string())),
+        };
+
+        let integer = match value {
+            SignalValue::Label(label) => self
+                .label_to_integer(label)
+                .ok_or_else(|| EncodeError::UnknownLabel(name.to_string(), label.clone()))?,
+            SignalValue::Physical(physical) => {
+                if let Some(min) = self.min
+                    && *physical < min
+                {
+                    return Err(EncodeError::BelowMin(name.to_string(), *physical, min));
+                }
+                if let Some(max) = self.max
+                    && *physical > max
+                {
+                    return Err(EncodeError::AboveMax(name.to_string(), *physical, max));
+                }
+                let scaled = (physical - self.offset.unwrap_or(0.0)) / self.factor.unwrap_or(1.0);
+                scaled.round() as i64
+            }
+        };
+
+        let (low, high) = self.integer_range(range.width());
+        if integer < low || integer > high {
+            let physical = match value {
+                SignalValue::Physical(physical) => *physical,
+                SignalValue::Label(_) => integer as f64,
+            };
+            return Err(EncodeError::NotEncodable(name.to_string(), physical));
+        }
+
+        range
+            .insert(payload, integer as u64)
+            .ok_or_else(|| EncodeError::BeyondLength(name.to_string()))
+    }
+}
+
+impl CanMessage {
+    /// Builds a payload of `length` bytes from signal values. Signals that are
+    /// not given are left at zero.
+    pub fn encode(&self, values: &HashMap<String, SignalValue>) -> Result<Vec<u8>, EncodeError> {
+        let length = match self.length {
+            Some(length) if length >= 0 => length as usize,
+            _ => return Err(EncodeError::MissingLength),
+        };
+        let mut payload = vec![0u8; length];
+
+        for name in values.keys() {
+            if !self
+                .signals
+                .iter()
+                .any(|(signal_name, _)| signal_name == name)
+            {
+                return Err(EncodeError::UnknownSignal(name.clone()));
+            }
+        }
+
+        for (name, signal) in &self.signals {
+            if let Some(value) = values.get(name) {
+                signal.encode(name, value, &mut payload)?;
+            }
+        }
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "id: 0x0F6
+length: 8
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    factor: 0.5
+    offset: -40
+    min: -40
+    max: 80
+  ACCEL:
+    bits: \"3.7-4.0\"
+    signed: true
+  LEVEL:
+    bits: \"5.7-5.4\"
+  GEAR:
+    bits: \"5.3-5.0\"
+    type: enum
+    values:
+      0:
+        en: \"park\"
+      1:
+        en: \"reverse\"
+        fr: \"marche arrière\"
+";
+
+    fn message() -> CanMessage {
+        CanMessage::from_yaml_str(MESSAGE, None).unwrap().0
+    }
+
+    fn encode(values: &[(&str, SignalValue)]) -> Result<Vec<u8>, EncodeError> {
+        let values = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        message().encode(&values)
+    }
+
+    /// Encodes a value, decodes it back and returns the decoded physical value.
+    fn round_trip(name: &str, value: SignalValue) -> f64 {
+        let payload = encode(&[(name, value)]).unwrap();
+        let message = message();
+        let (_, signal) = message.signals.iter().find(|(n, _)| n == name).unwrap();
+        signal.decode(name, &payload, &["en"]).unwrap().value
+    }
+
+    #[test]
+    fn factor_and_offset_round_trip() {
+        assert_eq!(
+            encode(&[("TEMP", SignalValue::Physical(25.0))]).unwrap(),
+            [0, 130, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(round_trip("TEMP", SignalValue::Physical(-12.5)), -12.5);
+    }
+
+    #[test]
+    fn signed_round_trip() {
+        assert_eq!(
+            encode(&[("ACCEL", SignalValue::Physical(-2.0))]).unwrap(),
+            [0, 0, 0xFF, 0xFE, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            round_trip("ACCEL", SignalValue::Physical(-32768.0)),
+            -32768.0
+        );
+        assert_eq!(round_trip("ACCEL", SignalValue::Physical(32767.0)), 32767.0);
+    }
+
+    #[test]
+    fn labels_in_any_language() {
+        let label = |text: &str| SignalValue::Label(String::from(text));
+        assert_eq!(round_trip("GEAR", label("reverse")), 1.0);
+        assert_eq!(round_trip("GEAR", label("marche arrière")), 1.0);
+        assert!(matches!(
+            encode(&[("GEAR", label("drive"))]),
+            Err(EncodeError::UnknownLabel(_, _))
+        ));
+    }
+
+    #[test]
+    fn signals_sharing_a_byte() {
+        let payload = encode(&[
+            ("LEVEL", SignalValue::Physical(0xA as f64)),
+            ("GEAR", SignalValue::Physical(1.0)),
+        ])
+        .unwrap();
+        assert_eq!(payload[4], 0xA1);
+    }
+
+    #[test]
+    fn range_errors() {
+        assert!(matches!(
+            encode(&[("TEMP", SignalValue::Physical(-41.0))]),
+            Err(EncodeError::BelowMin(_, _, _))
+        ));
+        assert!(matches!(
+            encode(&[("TEMP", SignalValue::Physical(81.0))]),
+            Err(EncodeError::AboveMax(_, _, _))
+        ));
+        assert!(matches!(
+            encode(&[("LEVEL", SignalValue::Physical(16.0))]),
+            Err(EncodeError::NotEncodable(_, _))
+        ));
+        assert!(matches!(
+            encode(&[("ACCEL", SignalValue::Physical(32768.0))]),
+            Err(EncodeError::NotEncodable(_, _))
+        ));
+        assert!(matches!(
+            encode(&[("SPEED", SignalValue::Physical(0.0))]),
+            Err(EncodeError::UnknownSignal(_))
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// CAN identifier, 11 bits for standard frames and 29 bits for extended ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CanId {
+    Standard(u16),
+    Extended(u32),
+}
+
+pub const STANDARD_MAX: u32 = 0x7FF;
+pub const EXTENDED_MAX: u32 = 0x1FFF_FFFF;
+
+impl CanId {
+    /// Builds an identifier from its numeric value, as standard when it fits in 11 bits.
+    pub fn from_raw(raw: u32) -> Option<CanId> {
+        if raw <= STANDARD_MAX {
+            Some(CanId::Standard(raw as u16))
+        } else if raw <= EXTENDED_MAX {
+            Some(CanId::Extended(raw))
+        } else {
+            None
+        }
+    }
+
+    /// Parses hexadecimal text with or without "0x", e.g. "0x0F6" or "0F6".
+    /// Eight digits always give an extended identifier.
+    pub fn parse(text: &str) -> Option<CanId> {
+        let text = text.trim();
+        let digits = text
+            .strip_prefix("0x")
+            .or_else(|| text.strip_prefix("0X"))
+            .unwrap_or(text);
+        let raw = u32::from_str_radix(digits, 16).ok()?;
+        if digits.len() == 8 && raw <= EXTENDED_MAX {
+            Some(CanId::Extended(raw))
+        } else {
+            Self::from_raw(raw)
+        }
+    }
+
+    /// Builds an identifier from a YAML integer.
+    pub fn from_integer(value: i64) -> Option<CanId> {
+        u32::try_from(value).ok().and_then(Self::from_raw)
+    }
+
+    pub fn raw(&self) -> u32 {
+        match self {
+            CanId::Standard(raw) => *raw as u32,
+            CanId::Extended(raw) => *raw,
+        }
+    }
+
+    pub fn is_extended(&self) -> bool {
+        matches!(self, CanId::Extended(_))
+    }
+
+    /// True if a received frame carries this identifier.
+    pub fn matches(&self, raw: u32, extended: bool) -> bool {
+        self.raw() == raw && self.is_extended() == extended
+    }
+}
+
+impl Ord for CanId {
+    fn cmp(&self, other: &CanId) -> Ordering {
+        self.raw()
+            .cmp(&other.raw())
+            .then(self.is_extended().cmp(&other.is_extended()))
+    }
+}
+
+impl PartialOrd for CanId {
+    fn partial_cmp(&self, other: &CanId) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for CanId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanId::Standard(raw) => write!(f, "0x{:03X}", raw),
+            CanId::Extended(raw) => write!(f, "0x{:08X}", raw),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hexadecimal() {
+        assert_eq!(CanId::parse("36"), Some(CanId::Standard(0x36)));
+        assert_eq!(CanId::parse("0x0F6"), Some(CanId::Standard(0xF6)));
+        assert_eq!(CanId::parse(" 0X7ff "), Some(CanId::Standard(0x7FF)));
+        assert_eq!(CanId::parse("0x800"), Some(CanId::Extended(0x800)));
+    }
+
+    #[test]
+    fn eight_digits_are_extended() {
+        assert_eq!(CanId::parse("000007FF"), Some(CanId::Extended(0x7FF)));
+        assert_eq!(
+            CanId::parse("0x18DAF110"),
+            Some(CanId::Extended(0x18DA_F110))
+        );
+        assert_eq!(CanId::parse("20000000"), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(CanId::parse(""), None);
+        assert_eq!(CanId::parse("0x"), None);
+        assert_eq!(CanId::parse("0F6G"), None);
+        assert_eq!(CanId::parse("-1"), None);
+    }
+
+    #[test]
+    fn display_is_canonical() {
+        assert_eq!(CanId::parse("36").unwrap().to_string(), "0x036");
+        assert_eq!(CanId::parse("000007FF").unwrap().to_string(), "0x000007FF");
+    }
+}
@@ -0,0 +1,397 @@
+use super::CanMessage;
+use super::Translation;
+use super::diagnostic::{Diagnostic, Diagnostics, field_path};
+use super::id::CanId;
+use std::collections::HashMap;
+use std::path::Path;
+
+fn lint_translation(
+    translation: &Translation,
+    languages: &[&str],
+    diags: &mut Diagnostics,
+    path: &str,
+) {
+    for lang in languages {
+        if translation.get(lang).is_none() {
+            diags.warning(path, format!("Missing \"{}\" translation.", lang));
+        }
+    }
+}
+
+fn lint_message(message: &CanMessage, languages: &[&str], diags: &mut Diagnostics) {
+    if let (Some(file_path), Some(id)) = (&message.file_path, &message.id) {
+        let stem = Path::new(file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("");
+        if CanId::parse(stem).is_none_or(|stem_id| stem_id.raw() != id.raw()) {
+            diags.warning(
+                "id",
+                format!("File name \"{}\" does not match id {}.", stem, id),
+            );
+        }
+    }
+
+    if let Some(comment) = &message.comment {
+        lint_translation(comment, languages, diags, "comment");
+    }
+
+    let mut previous = Vec::new();
+    for (name, signal) in &message.signals {
+        let path = field_path("signals", name);
+
+        if let (Some(min), Some(max)) = (signal.min, signal.max)
+            && min > max
+        {
+            diags.error(
+                &field_path(&path, "min"),
+                format!("\"min\" ({}) is greater than \"max\" ({}).", min, max),
+            );
+        }
+
+        if let Some(comment) = &signal.comment {
+            lint_translation(comment, languages, diags, &field_path(&path, "comment"));
+        }
+        for (value, translation) in &signal.values {
+            let value_path = field_path(&field_path(&path, "values"), &value.to_string());
+            match translation {
+                Some(translation) => lint_translation(translation, languages, diags, &value_path),
+                None => diags.warning(&value_path, "Value has no label."),
+            }
+        }
+
+        let Some(Ok(range)) = &signal.bits else {
+            continue;
+        };
+        let bits_path = field_path(&path, "bits");
+
+        if let Some(length) = message.length
+            && range.last_byte() as i64 > length
+        {
+            diags.error(
+                &bits_path,
+                format!(
+                    "Bits \"{}\" go beyond the message length of {} bytes.",
+                    range, length
+                ),
+            );
+        }
+
+        for (other_name, other_range) in &previous {
+            if range.overlaps(other_range) {
+                diags.warning(
+                    &bits_path,
+                    format!("Bits overlap with signal \"{}\".", other_name),
+                );
+            }
+        }
+        previous.push((name, *range));
+
+        let (low, high) = signal.integer_range(range.width());
+        for (value, _) in &signal.values {
+            if *value < low || *value > high {
+                diags.warning(
+                    &field_path(&field_path(&path, "values"), &value.to_string()),
+                    format!(
+                        "Value {} cannot be encoded in {} bit(s).",
+                        value,
+                        range.width()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Reports ids and names defined in more than one file.
+fn lint_duplicates(messages: &[CanMessage], per_message: &mut [Diagnostics]) {
+    let mut by_id: HashMap<CanId, Vec<usize>> = HashMap::new();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, message) in messages.iter().enumerate() {
+        if let Some(id) = message.id {
+            by_id.entry(id).or_default().push(index);
+        }
+        if let Some(name) = &message.name {
+            by_name.entry(name).or_default().push(index);
+        }
+    }
+
+    let file_of = |index: usize| messages[index].file_path.as_deref().unwrap_or("?");
+    for (field, groups) in [
+        ("id", by_id.into_values().collect::<Vec<_>>()),
+        ("name", by_name.into_values().collect::<Vec<_>>()),
+    ] {
+        for group in groups.iter().filter(|group| group.len() > 1) {
+            for &index in group {
+                let others: Vec<&str> = group
+                    .iter()
+                    .filter(|&&other| other != index)
+                    .map(|&other| file_of(other))
+                    .collect();
+                per_message[index].error(
+                    field,
+                    format!(
+                        "Duplicate {}, also defined in {}.",
+                        field,
+                        others.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Checks loaded messages for problems that the parser does not catch.
+/// `languages` lists the translations every comment and value label should have.
+pub fn lint(messages: &[CanMessage], languages: &[&str]) -> Vec<Diagnostic> {
+    let mut per_message: Vec<Diagnostics> = messages
+        .iter()
+        .map(|message| {
+            Diagnostics::new(
+                message.source.as_deref().unwrap_or(""),
+                message.file_path.as_deref(),
+            )
+        })
+        .collect();
+
+    for (message, diags) in messages.iter().zip(per_message.iter_mut()) {
+        lint_message(message, languages, diags);
+    }
+    lint_duplicates(messages, &mut per_message);
+
+    per_message
+        .into_iter()
+        .flat_map(|diags| diags.list)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::can::diagnostic::Severity;
+
+    fn message(text: &str, file_path: &str) -> CanMessage {
+        let mut message = CanMessage::from_yaml_str(text, Some(file_path)).unwrap().0;
+        message.file_path = Some(file_path.to_string());
+        message
+    }
+
+    /// Severity, field and message of every finding.
+    fn findings(messages: &[CanMessage]) -> Vec<(Severity, String, String)> {
+        lint(messages, &["en"])
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.field, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn clean_message() {
+        let messages = [message(
+            "id: 0x036
+length: 2
+comment:
+  en: \"Info\"
+signals:
+  A:
+    bits: \"1.7-1.0\"
+  B:
+    bits: \"2.7-2.0\"
+",
+            "036.yml",
+        )];
+        assert_eq!(findings(&messages), []);
+    }
+
+    #[test]
+    fn overlapping_bits() {
+        let messages = [message(
+            "id: 0x036
+signals:
+  A:
+    bits: \"1.7-2.0\"
+  B:
+    bits: \"2.3-2.0\"
+",
+            "036.yml",
+        )];
+        assert_eq!(
+            findings(&messages),
+            [(
+                Severity::Warning,
+                String::from("signals.B.bits"),
+                String::from("Bits overlap with signal \"A\"."),
+            )]
+        );
+    }
+
+    #[test]
+    fn bits_beyond_length() {
+        let messages = [message(
+            "id: 0x036
+length: 2
+signals:
+  A:
+    bits: \"2.7-3.0\"
+",
+            "036.yml",
+        )];
+        assert_eq!(
+            findings(&messages),
+            [(
+                Severity::Error,
+                String::from("signals.A.bits"),
+                String::from("Bits \"2.7-3.0\" go beyond the message length of 2 bytes."),
+            )]
+        );
+    }
+
+    #[test]
+    fn min_above_max() {
+        let messages = [message(
+            "id: 0x036
+signals:
+  A:
+    bits: \"1.7-1.0\"
+    min: 10
+    max: 5
+",
+            "036.yml",
+        )];
+        assert_eq!(
+            findings(&messages),
+            [(
+                Severity::Error,
+                String::from("signals.A.min"),
+                String::from("\"min\" (10) is greater than \"max\" (5)."),
+            )]
+        );
+    }
+
+    #[test]
+    fn value_labels_outside_encodable_range() {
+        let messages = [message(
+            "id: 0x036
+signals:
+  A:
+    bits: \"1.1-1.0\"
+    values:
+      3:
+        en: \"both\"
+      4:
+        en: \"none\"
+  B:
+    bits: \"2.1-2.0\"
+    signed: true
+    values:
+      -2:
+        en: \"low\"
+      2:
+        en: \"high\"
+",
+            "036.yml",
+        )];
+        assert_eq!(
+            findings(&messages),
+            [
+                (
+                    Severity::Warning,
+                    String::from("signals.A.values.4"),
+                    String::from("Value 4 cannot be encoded in 2 bit(s)."),
+                ),
+                (
+                    Severity::Warning,
+                    String::from("signals.B.values.2"),
+                    String::from("Value 2 cannot be encoded in 2 bit(s)."),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_id_and_name() {
+        let messages = [
+            message("id: 0x036\nname: INFO\n", "036.yml"),
+            message("id: 0x036\nname: OTHER\n", "a/036.yml"),
+            message("id: 0x0F6\nname: INFO\n", "0F6.yml"),
+        ];
+        let found = findings(&messages);
+        assert_eq!(found.len(), 4);
+        assert!(found.contains(&(
+            Severity::Error,
+            String::from("id"),
+            String::from("Duplicate id, also defined in a/036.yml."),
+        )));
+        assert!(found.contains(&(
+            Severity::Error,
+            String::from("id"),
+            String::from("Duplicate id, also defined in 036.yml."),
+        )));
+        assert!(found.contains(&(
+            Severity::Error,
+            String::from("name"),
+            String::from("Duplicate name, also defined in 0F6.yml."),
+        )));
+        assert!(found.contains(&(
+            Severity::Error,
+            String::from("name"),
+            String::from("Duplicate name, also defined in 036.yml."),
+        )));
+    }
+
+    #[test]
+    fn file_name_not_matching_id() {
+        let messages = [
+            message("id: 0x036\n", "0F6.yml"),
+            message("id: 0x0F6\n", "BSI_INFO.yml"),
+            message("id: 0x128\n", "dir/128.yaml"),
+        ];
+        assert_eq!(
+            findings(&messages),
+            [
+                (
+                    Severity::Warning,
+                    String::from("id"),
+                    String::from("File name \"0F6\" does not match id 0x036."),
+                ),
+                (
+                    Severity::Warning,
+                    String::from("id"),
+                    String::from("File name \"BSI_INFO\" does not match id 0x0F6."),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_translations() {
+        let messages = [message(
+            "id: 0x036
+comment:
+  fr: \"Infos\"
+signals:
+  A:
+    bits: \"1.7-1.0\"
+    values:
+      0:
+        en: \"off\"
+      1:
+",
+            "036.yml",
+        )];
+        assert_eq!(
+            findings(&messages),
+            [
+                (
+                    Severity::Warning,
+                    String::from("comment"),
+                    String::from("Missing \"en\" translation."),
+                ),
+                (
+                    Severity::Warning,
+                    String::from("signals.A.values.1"),
+                    String::from("Value has no label."),
+                ),
+            ]
+        );
+    }
+}
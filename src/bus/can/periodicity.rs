@@ -0,0 +1,180 @@
+use std::fmt;
+
+/// How a message is transmitted on the bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Periodicity {
+    /// Sent every given number of milliseconds.
+    Periodic(u32),
+    /// Sent on events only.
+    Triggered,
+    /// Sent every given number of milliseconds and on events.
+    Mixed(u32),
+    /// Text that could not be understood, kept as written.
+    Unknown(String),
+}
+
+fn is_trigger(part: &str) -> bool {
+    matches!(
+        part,
+        "trigger" | "triggered" | "event" | "on event" | "on change" | "spontaneous"
+    )
+}
+
+/// Parses "100ms", "100 ms", "1s" or a bare number of milliseconds.
+fn parse_period(part: &str) -> Option<u32> {
+    if let Some(number) = part.strip_suffix("ms") {
+        number.trim().parse().ok()
+    } else if let Some(number) = part.strip_suffix('s') {
+        number
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .and_then(|seconds| seconds.checked_mul(1000))
+    } else {
+        part.parse().ok()
+    }
+}
+
+impl Periodicity {
+    /// Parses the forms found in PSA-RE: "100ms", "trigger", "100ms+trigger",
+    /// "100ms / trigger"... Unrecognized text gives `Unknown`.
+    pub fn parse(text: &str) -> Periodicity {
+        let lower = text.trim().to_lowercase();
+        let mut period = None;
+        let mut triggered = false;
+
+        for part in lower
+            .split(['+', '/', ','])
+            .flat_map(|part| part.split(" or "))
+        {
+            let part = part.trim();
+            if is_trigger(part) {
+                triggered = true;
+            } else if let Some(ms) = parse_period(part)
+                && period.is_none()
+            {
+                period = Some(ms);
+            } else {
+                return Periodicity::Unknown(text.to_string());
+            }
+        }
+
+        match (period, triggered) {
+            (Some(ms), false) => Periodicity::Periodic(ms),
+            (Some(ms), true) => Periodicity::Mixed(ms),
+            (None, true) => Periodicity::Triggered,
+            (None, false) => Periodicity::Unknown(text.to_string()),
+        }
+    }
+
+    pub fn from_integer(ms: i64) -> Periodicity {
+        match u32::try_from(ms) {
+            Ok(ms) => Periodicity::Periodic(ms),
+            Err(_) => Periodicity::Unknown(ms.to_string()),
+        }
+    }
+
+    /// Transmission period, if the message is sent periodically.
+    pub fn period_ms(&self) -> Option<u32> {
+        match self {
+            Periodicity::Periodic(ms) | Periodicity::Mixed(ms) => Some(*ms),
+            Periodicity::Triggered | Periodicity::Unknown(_) => None,
+        }
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        matches!(self, Periodicity::Triggered | Periodicity::Mixed(_))
+    }
+
+    /// Text in the PSA-RE notation.
+    pub fn to_psa_re(&self) -> String {
+        match self {
+            Periodicity::Periodic(ms) => format!("{}ms", ms),
+            Periodicity::Triggered => String::from("trigger"),
+            Periodicity::Mixed(ms) => format!("{}ms+trigger", ms),
+            Periodicity::Unknown(text) => text.clone(),
+        }
+    }
+}
+
+/// Short form for tables.
+impl fmt::Display for Periodicity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Periodicity::Periodic(ms) => write!(f, "{}ms", ms),
+            Periodicity::Triggered => write!(f, "trig"),
+            Periodicity::Mixed(ms) => write!(f, "{}ms+trig", ms),
+            Periodicity::Unknown(_) => write!(f, "?"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periods() {
+        assert_eq!(Periodicity::parse("100ms"), Periodicity::Periodic(100));
+        assert_eq!(Periodicity::parse("100 ms"), Periodicity::Periodic(100));
+        assert_eq!(Periodicity::parse("1s"), Periodicity::Periodic(1000));
+        assert_eq!(Periodicity::parse("20"), Periodicity::Periodic(20));
+        assert_eq!(Periodicity::parse(" 50MS "), Periodicity::Periodic(50));
+    }
+
+    #[test]
+    fn triggers() {
+        for text in [
+            "trigger",
+            "triggered",
+            "event",
+            "on event",
+            "on change",
+            "spontaneous",
+            "Trigger",
+        ] {
+            assert_eq!(Periodicity::parse(text), Periodicity::Triggered, "{}", text);
+        }
+    }
+
+    #[test]
+    fn periods_and_triggers() {
+        for text in [
+            "100ms+trigger",
+            "100ms / trigger",
+            "100ms, event",
+            "100ms or on change",
+            "trigger+100ms",
+        ] {
+            assert_eq!(
+                Periodicity::parse(text),
+                Periodicity::Mixed(100),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_text_is_kept() {
+        for text in ["", "sometimes", "100ms+200ms", "-5ms"] {
+            assert_eq!(
+                Periodicity::parse(text),
+                Periodicity::Unknown(text.to_string()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn psa_re_notation_round_trip() {
+        for text in ["100ms", "trigger", "100ms+trigger", "sometimes"] {
+            assert_eq!(Periodicity::parse(text).to_psa_re(), text);
+        }
+        assert_eq!(
+            Periodicity::from_integer(-1),
+            Periodicity::Unknown(String::from("-1"))
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use super::{CanMessage, Signal, Translation};
+use log::warn;
+use std::fmt::Write as _;
+use std::fs;
+use yaml_rust2::{Yaml, YamlEmitter};
+
+const INDENT: &str = "  ";
+
+/// Always double-quoted string, as used upstream for comments and bits.
+fn quoted(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+    )
+}
+
+/// Plain scalar when YAML would read it back as the same string, quoted otherwise.
+fn scalar(text: &str) -> String {
+    let plain = !text.is_empty()
+        && !text.starts_with(['-', '.'])
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && matches!(Yaml::from_str(text), Yaml::String(ref plain) if plain == text)
+        // Other YAML readers also take these as booleans or null, whatever the case
+        && !matches!(
+            text.to_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null"
+        );
+    if plain {
+        text.to_string()
+    } else {
+        quoted(text)
+    }
+}
+
+fn flow_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| scalar(item)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Number as written in PSA-RE, `None` for NaN and infinities, which it has
+/// no notation for.
+fn number(value: f64) -> Option<String> {
+    value.is_finite().then(|| format!("{}", value))
+}
+
+impl Translation {
+    fn write_yaml(&self, out: &mut String, indent: usize) {
+        for (lang, text) in self.iter() {
+            let _ = writeln!(
+                out,
+                "{}{}: {}",
+                INDENT.repeat(indent),
+                scalar(lang),
+                quoted(text)
+            );
+        }
+    }
+}
+
+/// Writes a parameter this client does not model, as it was loaded.
+fn write_extra(out: &mut String, pad: &str, key: &str, value: &Yaml) {
+    match value {
+        Yaml::String(text) => {
+            let _ = writeln!(out, "{}{}: {}", pad, scalar(key), scalar(text));
+        }
+        Yaml::Integer(number) => {
+            let _ = writeln!(out, "{}{}: {}", pad, scalar(key), number);
+        }
+        Yaml::Real(number) => {
+            let _ = writeln!(out, "{}{}: {}", pad, scalar(key), number);
+        }
+        Yaml::Boolean(flag) => {
+            let _ = writeln!(out, "{}{}: {}", pad, scalar(key), flag);
+        }
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => {
+            let _ = writeln!(out, "{}{}: ~", pad, scalar(key));
+        }
+        Yaml::Array(_) | Yaml::Hash(_) => {
+            let mut emitted = String::new();
+            if YamlEmitter::new(&mut emitted).dump(value).is_err() {
+                return;
+            }
+            let _ = writeln!(out, "{}{}:", pad, scalar(key));
+            for line in emitted.lines().filter(|line| *line != "---") {
+                let _ = writeln!(out, "{}{}{}", pad, INDENT, line);
+            }
+        }
+    }
+}
+
+impl Signal {
+    fn write_yaml(&self, out: &mut String, indent: usize) {
+        let pad = INDENT.repeat(indent);
+        if let Some(alt_names) = &self.alt_names {
+            let _ = writeln!(out, "{}alt_names: {}", pad, flow_list(alt_names));
+        }
+        match &self.bits {
+            Some(Ok(bits)) => {
+                let _ = writeln!(out, "{}bits: {}", pad, quoted(&bits.to_string()));
+            }
+            Some(Err(err)) => {
+                let _ = writeln!(out, "{}bits: {}", pad, quoted(err.text()));
+            }
+            None => {}
+        }
+        if let Some(data_type) = &self.data_type {
+            let _ = writeln!(out, "{}type: {}", pad, scalar(data_type));
+        }
+        if let Some(signed) = self.signed {
+            let _ = writeln!(out, "{}signed: {}", pad, signed);
+        }
+        for (key, value) in [
+            ("factor", self.factor),
+            ("offset", self.offset),
+            ("min", self.min),
+            ("max", self.max),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            match number(value) {
+                Some(text) => {
+                    let _ = writeln!(out, "{}{}: {}", pad, key, text);
+                }
+                None => warn!("Not writing \"{}\", {} is not a finite number.", key, value),
+            }
+        }
+        if let Some(units) = &self.units {
+            let _ = writeln!(out, "{}units: {}", pad, quoted(units));
+        }
+        if let Some(comment) = &self.comment {
+            let _ = writeln!(out, "{}comment:", pad);
+            comment.write_yaml(out, indent + 1);
+        }
+        if !self.values.is_empty() {
+            let _ = writeln!(out, "{}values:", pad);
+            for (value, translation) in &self.values {
+                match translation {
+                    // An empty mapping reads back as an empty label, nothing as no label
+                    Some(translation) if translation.iter().next().is_none() => {
+                        let _ = writeln!(out, "{}{}{}: {{}}", pad, INDENT, value);
+                    }
+                    Some(translation) => {
+                        let _ = writeln!(out, "{}{}{}:", pad, INDENT, value);
+                        translation.write_yaml(out, indent + 2);
+                    }
+                    None => {
+                        let _ = writeln!(out, "{}{}{}:", pad, INDENT, value);
+                    }
+                }
+            }
+        }
+        if let Some(unused) = self.unused {
+            let _ = writeln!(out, "{}unused: {}", pad, unused);
+        }
+        for (key, value) in &self.extra {
+            write_extra(out, &pad, key, value);
+        }
+    }
+
+    /// Signal block with its name at the first column.
+    pub fn to_yaml_block(&self, name: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}:", scalar(name));
+        self.write_yaml(&mut out, 1);
+        out
+    }
+}
+
+/// Top level parameters of a message, in the order used by PSA-RE.
+const MESSAGE_FIELDS: [&str; 10] = [
+    "id",
+    "name",
+    "alt_names",
+    "length",
+    "comment",
+    "type",
+    "periodicity",
+    "senders",
+    "receivers",
+    "signals",
+];
+
+/// Part of a YAML file describing one key, with the comments preceding it.
+struct Segment {
+    key: Option<String>,
+    text: String,
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_key_line(line: &str, indent: usize) -> bool {
+    let trimmed = line.trim();
+    indentation(line) == indent
+        && !trimmed.is_empty()
+        && !trimmed.starts_with('#')
+        && trimmed != "---"
+        && trimmed != "..."
+}
+
+fn key_of(line: &str) -> String {
+    let key = line.trim().split(':').next().unwrap_or("").trim();
+    key.trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+/// Splits lines into segments starting at each key of the given indentation.
+/// Comments and blank lines are attached to the key that follows them; the
+/// text before the first key and after the last one has no key.
+fn split_segments(lines: &[&str], indent: usize) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut pending = String::new();
+    let mut current: Option<Segment> = None;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if is_key_line(line, indent) {
+            if let Some(segment) = current.take() {
+                segments.push(segment);
+            }
+            let mut text = std::mem::take(&mut pending);
+            text.push_str(line);
+            current = Some(Segment {
+                key: Some(key_of(line)),
+                text,
+            });
+        } else if trimmed.is_empty() || trimmed.starts_with('#') {
+            pending.push_str(line);
+        } else if let Some(segment) = current.as_mut() {
+            segment.text.push_str(&std::mem::take(&mut pending));
+            segment.text.push_str(line);
+        } else {
+            pending.push_str(line);
+        }
+    }
+    if let Some(segment) = current {
+        segments.push(segment);
+    }
+    if !pending.is_empty() {
+        segments.push(Segment {
+            key: None,
+            text: pending,
+        });
+    }
+    segments
+}
+
+/// Leading comments and blank lines of a segment.
+fn leading_comments(text: &str) -> String {
+    text.split_inclusive('\n')
+        .take_while(|line| line.trim().is_empty() || line.trim().starts_with('#'))
+        .collect()
+}
+
+/// Generated text with the line ending of the file it goes into.
+fn with_newline(text: &str, newline: &str) -> String {
+    text.replace('\n', newline)
+}
+
+/// Indents a block written with `INDENT` by `indent` spaces, using `step`
+/// spaces per nesting level.
+fn reindent(text: &str, indent: usize, step: usize) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let depth = indentation(line) / INDENT.len();
+            format!(
+                "{}{}",
+                " ".repeat(indent + depth * step),
+                &line[depth * INDENT.len()..]
+            )
+        })
+        .collect()
+}
+
+impl CanMessage {
+    /// Text of one top level parameter, `None` if it is not set.
+    fn field_yaml(&self, key: &str) -> Option<String> {
+        let mut out = String::new();
+        match key {
+            "id" => {
+                let _ = writeln!(out, "id: {}", self.id?);
+            }
+            "name" => {
+                let _ = writeln!(out, "name: {}", scalar(self.name.as_ref()?));
+            }
+            "alt_names" => {
+                let _ = writeln!(out, "alt_names: {}", flow_list(self.alt_names.as_ref()?));
+            }
+            "length" => {
+                let _ = writeln!(out, "length: {}", self.length?);
+            }
+            "comment" => {
+                out.push_str("comment:\n");
+                self.comment.as_ref()?.write_yaml(&mut out, 1);
+            }
+            "type" => {
+                let _ = writeln!(out, "type: {}", scalar(self.bus_type.as_ref()?));
+            }
+            "periodicity" => {
+                let periodicity = self.periodicity.as_ref()?;
+                let _ = writeln!(out, "periodicity: {}", scalar(&periodicity.to_psa_re()));
+            }
+            "senders" if !self.senders.is_empty() => {
+                let _ = writeln!(out, "senders: {}", flow_list(&self.senders));
+            }
+            "receivers" if !self.receivers.is_empty() => {
+                let _ = writeln!(out, "receivers: {}", flow_list(&self.receivers));
+            }
+            "signals" if !self.signals.is_empty() => {
+                out.push_str("signals:\n");
+                for (name, signal) in &self.signals {
+                    out.push_str(&reindent(
+                        &signal.to_yaml_block(name),
+                        INDENT.len(),
+                        INDENT.len(),
+                    ));
+                }
+            }
+            _ => {
+                let (_, value) = self.extra.iter().find(|(k, _)| k == key)?;
+                write_extra(&mut out, "", key, value);
+            }
+        }
+        if out.is_empty() { None } else { Some(out) }
+    }
+
+    /// Serializes the message in the layout used by the PSA-RE repository.
+    pub fn to_yaml_string(&self) -> String {
+        let mut out = String::new();
+        for key in MESSAGE_FIELDS {
+            if let Some(text) = self.field_yaml(key) {
+                out.push_str(&text);
+            }
+        }
+        for (key, value) in &self.extra {
+            write_extra(&mut out, "", key, value);
+        }
+        out
+    }
+
+    /// Serializes the message, reusing the text it was loaded from for every
+    /// parameter and signal that did not change, so comments, unknown
+    /// parameters and formatting are kept.
+    pub fn to_yaml_string_preserving(&self) -> String {
+        let Some(source) = &self.source else {
+            return self.to_yaml_string();
+        };
+        let Ok((original, _)) = CanMessage::from_yaml_str(source, None) else {
+            return self.to_yaml_string();
+        };
+
+        let newline = if source.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let lines: Vec<&str> = source.split_inclusive('\n').collect();
+        let mut out = String::new();
+        let mut written: Vec<String> = Vec::new();
+
+        for segment in split_segments(&lines, 0) {
+            let Some(key) = &segment.key else {
+                out.push_str(&segment.text);
+                continue;
+            };
+            if !written.contains(key) {
+                written.push(key.clone());
+            }
+
+            if key == "signals" {
+                // New parameters are written before the signals
+                self.write_missing_fields(&mut out, &mut written, newline);
+                if let Some(text) = self.signals_yaml_preserving(&original, &segment.text, newline)
+                {
+                    out.push_str(&text);
+                }
+                continue;
+            }
+
+            let new_text = self.field_yaml(key);
+            if new_text == original.field_yaml(key) {
+                out.push_str(&segment.text);
+            } else if let Some(new_text) = new_text {
+                out.push_str(&leading_comments(&segment.text));
+                out.push_str(&with_newline(&new_text, newline));
+            }
+        }
+        self.write_missing_fields(&mut out, &mut written, newline);
+        out
+    }
+
+    fn write_missing_fields(&self, out: &mut String, written: &mut Vec<String>, newline: &str) {
+        let extra_keys = self.extra.iter().map(|(key, _)| key.as_str());
+        for key in MESSAGE_FIELDS.into_iter().chain(extra_keys) {
+            if written.iter().any(|k| k == key) {
+                continue;
+            }
+            if let Some(text) = self.field_yaml(key) {
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push_str(newline);
+                }
+                out.push_str(&with_newline(&text, newline));
+                written.push(key.to_string());
+            }
+        }
+    }
+
+    /// Rewrites the `signals` segment, keeping the text of unchanged signals.
+    fn signals_yaml_preserving(
+        &self,
+        original: &CanMessage,
+        segment: &str,
+        newline: &str,
+    ) -> Option<String> {
+        if self.signals.is_empty() {
+            // Keeps an explicit empty `signals: {}`
+            return original.signals.is_empty().then(|| segment.to_string());
+        }
+
+        let lines: Vec<&str> = segment.split_inclusive('\n').collect();
+        let header_end = lines
+            .iter()
+            .position(|line| line.trim_start().starts_with("signals"))?;
+        let body = &lines[header_end + 1..];
+        let Some(indent) = body
+            .iter()
+            .find(|line| is_key_line(line, indentation(line)))
+            .map(|line| indentation(line))
+        else {
+            // Flow style or empty signals, nothing to preserve
+            return self.field_yaml("signals").map(|text| {
+                let mut out: String = lines[..header_end].concat();
+                out.push_str(&with_newline(&text, newline));
+                out
+            });
+        };
+
+        // Nesting step of the file, from the first line inside a signal
+        let step = body
+            .iter()
+            .map(|line| (indentation(line), line.trim()))
+            .find(|(line_indent, trimmed)| {
+                *line_indent > indent && !trimmed.is_empty() && !trimmed.starts_with('#')
+            })
+            .map_or(INDENT.len(), |(line_indent, _)| line_indent - indent);
+
+        let mut out: String = lines[..=header_end].concat();
+        let mut written: Vec<&str> = Vec::new();
+
+        for sub in split_segments(body, indent) {
+            let Some(name) = &sub.key else {
+                out.push_str(&sub.text);
+                continue;
+            };
+            let Some((name, signal)) = self.signals.iter().find(|(n, _)| n == name) else {
+                continue;
+            };
+            written.push(name);
+            let new_text = signal.to_yaml_block(name);
+            let unchanged = original
+                .signals
+                .iter()
+                .any(|(n, s)| n == name && s.to_yaml_block(n) == new_text);
+            if unchanged {
+                out.push_str(&sub.text);
+            } else {
+                out.push_str(&leading_comments(&sub.text));
+                out.push_str(&with_newline(&reindent(&new_text, indent, step), newline));
+            }
+        }
+
+        for (name, signal) in &self.signals {
+            if !written.contains(&name.as_str()) {
+                if !out.ends_with('\n') {
+                    out.push_str(newline);
+                }
+                out.push_str(&with_newline(
+                    &reindent(&signal.to_yaml_block(name), indent, step),
+                    newline,
+                ));
+            }
+        }
+        Some(out)
+    }
+
+    /// Saves the message back to the file it was loaded from, which becomes
+    /// the new reference for later saves.
+    pub fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = self
+            .file_path
+            .clone()
+            .ok_or("CAN message was not loaded from a file.")?;
+        let yaml_str = self.to_yaml_string_preserving();
+        fs::write(&file_path, &yaml_str)?;
+        self.source = Some(yaml_str);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::can::bits::BitRange;
+
+    const SOURCE: &str = "# Header comment
+id: 0x1A8
+name: CRUISE_CONTROL
+length: 8
+periodicity: trigger
+x_custom_field: [1, 2]
+senders: [BSI]
+signals:
+    # Speed setpoint
+    SPEED_SETPOINT:
+        bits: \"1.7-2.0\"
+        factor: 0.1
+        x_note: keep me
+    # State
+    STATE:
+        bits: \"3.7-3.6\"
+        values:
+            0:
+                en: \"off\"
+# trailing comment
+";
+
+    fn load(text: &str) -> CanMessage {
+        CanMessage::from_yaml_str(text, None).unwrap().0
+    }
+
+    #[test]
+    fn unchanged_file_is_kept() {
+        assert_eq!(load(SOURCE).to_yaml_string_preserving(), SOURCE);
+    }
+
+    #[test]
+    fn unchanged_crlf_file_is_kept() {
+        let source = SOURCE.replace('\n', "\r\n");
+        assert_eq!(load(&source).to_yaml_string_preserving(), source);
+    }
+
+    #[test]
+    fn editing_a_signal_keeps_the_others() {
+        let mut message = load(SOURCE);
+        message.signals[1].1.values[0].0 = 1;
+        let saved = message.to_yaml_string_preserving();
+
+        assert!(saved.contains("    # Speed setpoint\n    SPEED_SETPOINT:\n"));
+        assert!(saved.contains("        x_note: keep me\n"));
+        assert!(saved.contains("    # State\n    STATE:\n"));
+        assert!(saved.starts_with("# Header comment\n"));
+        assert!(saved.ends_with("# trailing comment\n"));
+        assert_eq!(load(&saved).signals[1].1.values[0].0, 1);
+    }
+
+    #[test]
+    fn edited_signals_keep_the_file_indentation() {
+        let mut message = load(SOURCE);
+        message.signals[1].1.values[0].0 = 1;
+        let mut added = message.signals[1].1.clone();
+        added.bits = Some(BitRange::parse("4.7-4.0"));
+        message.signals.push((String::from("MODE"), added));
+        let saved = message.to_yaml_string_preserving();
+
+        assert!(saved.contains(
+            "    STATE:
+        bits: \"3.7-3.6\"
+        values:
+            1:
+                en: \"off\"
+    MODE:
+        bits: \"4.7-4.0\"
+        values:
+"
+        ));
+        assert_eq!(load(&saved).signals.len(), 3);
+    }
+
+    #[test]
+    fn edited_crlf_file_keeps_crlf() {
+        let mut message = load(&SOURCE.replace('\n', "\r\n"));
+        message.signals[1].1.values[0].0 = 1;
+        message.length = Some(7);
+        let saved = message.to_yaml_string_preserving();
+
+        assert!(saved.contains("length: 7\r\n"));
+        assert_eq!(saved.matches('\n').count(), saved.matches("\r\n").count());
+    }
+
+    #[test]
+    fn whole_message_round_trip() {
+        let source = "name: BSI_INFO
+id: 0x0F6
+senders:
+  - BSI
+receivers: [CMB, \"0x10\"]
+periodicity: 100ms
+length: 8
+alt_names: [DONNEES_BSI_LENTES]
+comment:
+  en: \"General \\\"info\\\"\"
+  fr: Infos
+type: can
+signals:
+  TEMP:
+    max: 87.5
+    bits: \"2.7-2.0\"
+    factor: 0.5
+    offset: -40
+    min: -40
+    units: \"°C\"
+    signed: false
+    comment:
+      en: \"Coolant\\ntemperature\"
+  TURN:
+    bits: \"8.1-8.0\"
+    type: enum
+    values:
+      0:
+        en: \"off\"
+        fr: \"arrêt\"
+      3: {}
+      5:
+    unused: true
+";
+        let mut message = load(source);
+        let yaml = message.to_yaml_string();
+        assert_eq!(
+            yaml,
+            "id: 0x0F6
+name: BSI_INFO
+alt_names: [DONNEES_BSI_LENTES]
+length: 8
+comment:
+  en: \"General \\\"info\\\"\"
+  fr: \"Infos\"
+type: can
+periodicity: 100ms
+senders: [BSI]
+receivers: [CMB, \"0x10\"]
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    signed: false
+    factor: 0.5
+    offset: -40
+    min: -40
+    max: 87.5
+    units: \"°C\"
+    comment:
+      en: \"Coolant\\ntemperature\"
+  TURN:
+    bits: \"8.1-8.0\"
+    type: enum
+    values:
+      0:
+        en: \"off\"
+        fr: \"arrêt\"
+      3: {}
+      5:
+    unused: true
+"
+        );
+
+        let mut reloaded = load(&yaml);
+        message.source = None;
+        reloaded.source = None;
+        assert_eq!(format!("{:?}", reloaded), format!("{:?}", message));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_not_written() {
+        let mut message = load("id: 0x0F6\nsignals:\n  A:\n    bits: \"1.7-1.0\"\n    factor: 2\n");
+        message.signals[0].1.factor = Some(f64::NAN);
+        message.signals[0].1.offset = Some(f64::INFINITY);
+        message.signals[0].1.max = Some(10.0);
+        assert_eq!(
+            message.to_yaml_string(),
+            "id: 0x0F6\nsignals:\n  A:\n    bits: \"1.7-1.0\"\n    max: 10\n"
+        );
+    }
+
+    #[test]
+    fn scalars_that_read_back_differently_are_quoted() {
+        for text in [
+            "0x10", "0o17", "010", "1e3", ".inf", "true", "Null", "~", "yes", "",
+        ] {
+            assert_eq!(scalar(text), quoted(text), "{}", text);
+        }
+        for text in ["BSI", "CRUISE_CONTROL", "x10", "km"] {
+            assert_eq!(scalar(text), text);
+        }
+
+        let mut message = load("id: 0x10\n");
+        message.name = Some(String::from("0x10"));
+        assert_eq!(
+            load(&message.to_yaml_string()).name.as_deref(),
+            Some("0x10")
+        );
+    }
+
+    #[test]
+    fn empty_signals_are_kept() {
+        let source = "id: 0x10\nname: EMPTY\nsignals: {}\n";
+        let mut message = load(source);
+        assert_eq!(message.to_yaml_string_preserving(), source);
+
+        message.name = Some(String::from("RENAMED"));
+        assert_eq!(
+            message.to_yaml_string_preserving(),
+            "id: 0x10\nname: RENAMED\nsignals: {}\n"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crate::bus::can::diagnostic::field_path;
+use crate::bus::can::{CanMessage, Signal};
+use crate::bus::database::Database;
+
+/// Field whose value differs between two messages. `None` means the field,
+/// or the whole signal, is absent on that side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// Path of the field, e.g. `length` or `signals.SPEED.bits`.
+    pub field: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// Messages matched by name across two databases, with their differences.
+#[derive(Debug, Clone)]
+pub struct MessageComparison<'a> {
+    pub left: Option<&'a CanMessage>,
+    pub right: Option<&'a CanMessage>,
+    pub differences: Vec<Difference>,
+}
+
+impl MessageComparison<'_> {
+    pub fn is_identical(&self) -> bool {
+        self.left.is_some() && self.right.is_some() && self.differences.is_empty()
+    }
+}
+
+fn names(name: Option<&String>, alt_names: Option<&Vec<String>>) -> Vec<String> {
+    name.into_iter()
+        .chain(alt_names.into_iter().flatten())
+        .cloned()
+        .collect()
+}
+
+fn push_difference(
+    differences: &mut Vec<Difference>,
+    field: String,
+    left: Option<String>,
+    right: Option<String>,
+) {
+    if left != right {
+        differences.push(Difference { field, left, right });
+    }
+}
+
+/// Fields of a signal that make up its layout and scaling.
+fn signal_fields(signal: &Signal) -> [(&'static str, Option<String>); 5] {
+    [
+        (
+            "bits",
+            signal.bits.as_ref().map(|bits| match bits {
+                Ok(range) => range.to_string(),
+                Err(err) => err.text().to_string(),
+            }),
+        ),
+        ("type", signal.data_type.clone()),
+        ("signed", signal.signed.map(|signed| signed.to_string())),
+        ("factor", signal.factor.map(|factor| factor.to_string())),
+        ("offset", signal.offset.map(|offset| offset.to_string())),
+    ]
+}
+
+fn compare_signals(left: &CanMessage, right: &CanMessage, differences: &mut Vec<Difference>) {
+    let find_right = |name: &str, signal: &Signal| {
+        let names = names(Some(&name.to_string()), signal.alt_names.as_ref());
+        right.signals.iter().position(|(right_name, right_signal)| {
+            names.iter().any(|n| {
+                n == right_name
+                    || right_signal
+                        .alt_names
+                        .as_ref()
+                        .is_some_and(|alt_names| alt_names.contains(n))
+            })
+        })
+    };
+
+    let mut matched = Vec::new();
+    for (name, signal) in &left.signals {
+        let path = field_path("signals", name);
+        let Some(index) = find_right(name, signal) else {
+            push_difference(differences, path, Some(String::from("present")), None);
+            continue;
+        };
+        matched.push(index);
+        let (right_name, right_signal) = &right.signals[index];
+        if right_name != name {
+            push_difference(
+                differences,
+                field_path(&path, "name"),
+                Some(name.clone()),
+                Some(right_name.clone()),
+            );
+        }
+        for ((field, left_value), (_, right_value)) in signal_fields(signal)
+            .into_iter()
+            .zip(signal_fields(right_signal))
+        {
+            push_difference(
+                differences,
+                field_path(&path, field),
+                left_value,
+                right_value,
+            );
+        }
+    }
+    for (index, (name, _)) in right.signals.iter().enumerate() {
+        if !matched.contains(&index) {
+            push_difference(
+                differences,
+                field_path("signals", name),
+                None,
+                Some(String::from("present")),
+            );
+        }
+    }
+}
+
+/// Differences in name, id, length, periodicity and signal layout of two messages.
+pub fn compare_messages(left: &CanMessage, right: &CanMessage) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    push_difference(
+        &mut differences,
+        String::from("name"),
+        left.name.clone(),
+        right.name.clone(),
+    );
+    push_difference(
+        &mut differences,
+        String::from("id"),
+        left.id.map(|id| id.to_string()),
+        right.id.map(|id| id.to_string()),
+    );
+    push_difference(
+        &mut differences,
+        String::from("length"),
+        left.length.map(|length| length.to_string()),
+        right.length.map(|length| length.to_string()),
+    );
+    push_difference(
+        &mut differences,
+        String::from("periodicity"),
+        left.periodicity.as_ref().map(|p| p.to_psa_re()),
+        right.periodicity.as_ref().map(|p| p.to_psa_re()),
+    );
+    compare_signals(left, right, &mut differences);
+    differences
+}
+
+/// Matches the messages of two databases by name and alternative names, and
+/// compares each pair. Messages found on one side only come last.
+pub fn compare<'a>(left: &'a Database, right: &'a Database) -> Vec<MessageComparison<'a>> {
+    let mut comparisons = Vec::new();
+    let mut matched = Vec::new();
+    for message in left.messages() {
+        let right_index = names(message.name.as_ref(), message.alt_names.as_ref())
+            .iter()
+            .find_map(|name| right.index_of_name(name));
+        match right_index {
+            Some(index) => {
+                matched.push(index);
+                let right_message = &right.messages()[index];
+                comparisons.push(MessageComparison {
+                    left: Some(message),
+                    right: Some(right_message),
+                    differences: compare_messages(message, right_message),
+                });
+            }
+            None => comparisons.push(MessageComparison {
+                left: Some(message),
+                right: None,
+                differences: Vec::new(),
+            }),
+        }
+    }
+    for (index, message) in right.messages().iter().enumerate() {
+        if !matched.contains(&index) {
+            comparisons.push(MessageComparison {
+                left: None,
+                right: Some(message),
+                differences: Vec::new(),
+            });
+        }
+    }
+    comparisons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> CanMessage {
+        CanMessage::from_yaml_str(text, None).unwrap().0
+    }
+
+    fn database(texts: &[&str]) -> Database {
+        Database::new(texts.iter().map(|text| message(text)).collect())
+    }
+
+    fn difference(field: &str, left: Option<&str>, right: Option<&str>) -> Difference {
+        Difference {
+            field: field.to_string(),
+            left: left.map(String::from),
+            right: right.map(String::from),
+        }
+    }
+
+    const BSI_INFO: &str = "id: 0x0F6
+name: BSI_INFO
+length: 8
+periodicity: 100ms
+signals:
+  SPEED:
+    bits: \"1.7-2.0\"
+    type: uint
+    factor: 0.01
+    offset: 0
+";
+
+    #[test]
+    fn identical_messages_match_by_name() {
+        let left = database(&[BSI_INFO]);
+        let right = database(&[BSI_INFO]);
+        let comparisons = compare(&left, &right);
+        assert_eq!(comparisons.len(), 1);
+        assert!(comparisons[0].is_identical());
+    }
+
+    #[test]
+    fn messages_match_by_alt_names() {
+        let left = database(&["id: 0x0F6\nname: BSI_INFO\n"]);
+        let right = database(&["id: 0x0F6\nname: BSI_INFO_GEN\nalt_names: [BSI_INFO]\n"]);
+        let comparisons = compare(&left, &right);
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(
+            comparisons[0].differences,
+            [difference("name", Some("BSI_INFO"), Some("BSI_INFO_GEN"))]
+        );
+
+        let comparisons = compare(&right, &left);
+        assert_eq!(comparisons.len(), 1);
+        assert!(comparisons[0].right.is_some());
+    }
+
+    #[test]
+    fn message_differences() {
+        let right = message(
+            "id: 0x0F7
+name: BSI_INFO
+length: 7
+periodicity: 200ms
+signals:
+  SPEED:
+    bits: \"1.7-2.0\"
+    type: uint
+    factor: 0.01
+    offset: 0
+",
+        );
+        assert_eq!(
+            compare_messages(&message(BSI_INFO), &right),
+            [
+                difference("id", Some("0x0F6"), Some("0x0F7")),
+                difference("length", Some("8"), Some("7")),
+                difference("periodicity", Some("100ms"), Some("200ms")),
+            ]
+        );
+    }
+
+    #[test]
+    fn signal_differences() {
+        let right = message(
+            "id: 0x0F6
+name: BSI_INFO
+length: 8
+periodicity: 100ms
+signals:
+  SPEED:
+    bits: \"1.7-1.0\"
+    type: int
+    signed: true
+    factor: 0.1
+    offset: -10
+",
+        );
+        assert_eq!(
+            compare_messages(&message(BSI_INFO), &right),
+            [
+                difference("signals.SPEED.bits", Some("1.7-2.0"), Some("1.7-1.0")),
+                difference("signals.SPEED.type", Some("uint"), Some("int")),
+                difference("signals.SPEED.signed", None, Some("true")),
+                difference("signals.SPEED.factor", Some("0.01"), Some("0.1")),
+                difference("signals.SPEED.offset", Some("0"), Some("-10")),
+            ]
+        );
+    }
+
+    #[test]
+    fn signals_match_by_alt_names_or_are_reported_missing() {
+        let left = message(
+            "id: 0x0F6
+signals:
+  SPEED:
+    bits: \"1.7-1.0\"
+  RPM:
+    bits: \"2.7-2.0\"
+",
+        );
+        let right = message(
+            "id: 0x0F6
+signals:
+  VITESSE:
+    bits: \"1.7-1.0\"
+    alt_names: [SPEED]
+  TEMP:
+    bits: \"3.7-3.0\"
+",
+        );
+        assert_eq!(
+            compare_messages(&left, &right),
+            [
+                difference("signals.SPEED.name", Some("SPEED"), Some("VITESSE")),
+                difference("signals.RPM", Some("present"), None),
+                difference("signals.TEMP", None, Some("present")),
+            ]
+        );
+    }
+
+    #[test]
+    fn messages_on_one_side_come_last() {
+        let left = database(&["id: 0x036\nname: ONLY_LEFT\n", BSI_INFO]);
+        let right = database(&[BSI_INFO, "id: 0x128\nname: ONLY_RIGHT\n"]);
+        let comparisons = compare(&left, &right);
+        let sides: Vec<(Option<&str>, Option<&str>)> = comparisons
+            .iter()
+            .map(|comparison| {
+                (
+                    comparison.left.and_then(|message| message.name.as_deref()),
+                    comparison.right.and_then(|message| message.name.as_deref()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sides,
+            [
+                (Some("ONLY_LEFT"), None),
+                (Some("BSI_INFO"), Some("BSI_INFO")),
+                (None, Some("ONLY_RIGHT")),
+            ]
+        );
+        assert!(!comparisons[0].is_identical());
+        assert!(comparisons[1].is_identical());
+        assert!(!comparisons[2].is_identical());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::bus::can::diagnostic::Diagnostic;
+use crate::bus::can::id::CanId;
+use crate::bus::can::{CanMessage, Signal};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// True for a YAML file, the format of PSA-RE message and ECU descriptions.
+pub fn is_message_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "yml" || ext == "yaml")
+}
+
+/// Messages of one bus, indexed for lookups by id, name and signal name.
+#[derive(Debug, Clone, Default)]
+pub struct Database {
+    messages: Vec<CanMessage>,
+    by_id: HashMap<CanId, usize>,
+    /// Message names and alternative names.
+    by_name: HashMap<String, usize>,
+    /// Signal names and alternative names, to (message, signal) indexes.
+    by_signal: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Database {
+    /// Builds the database, sorting messages by id.
+    pub fn new(mut messages: Vec<CanMessage>) -> Database {
+        messages.sort_by_key(|message| message.id);
+        let mut database = Database {
+            messages,
+            ..Database::default()
+        };
+        database.reindex();
+        database
+    }
+
+    /// Loads every message file of a bus directory. Files that fail to load
+    /// are skipped and reported with the other problems.
+    pub fn load_dir(dir: &str) -> (Database, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let dir_entries = match fs::read_dir(dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) => {
+                diagnostics.push(Diagnostic::directory(
+                    dir,
+                    format!("Failed to read directory: {}.", err),
+                ));
+                return (Database::default(), diagnostics);
+            }
+        };
+
+        let mut messages = Vec::new();
+        for file_in_path in dir_entries.flatten() {
+            let f_path = file_in_path.path();
+
+            if is_message_file(&f_path)
+                && let Some(file_path_str) = f_path.to_str()
+            {
+                debug!("PSA-RE-CLIENT opening file {}.", file_path_str);
+                match CanMessage::from_yaml_file(file_path_str) {
+                    Ok((message, message_diagnostics)) => {
+                        messages.push(message);
+                        diagnostics.extend(message_diagnostics);
+                    }
+                    Err(diagnostic) => {
+                        warn!("Skipping {}: {}", file_path_str, diagnostic);
+                        diagnostics.push(diagnostic);
+                    }
+                }
+            }
+        }
+        (Database::new(messages), diagnostics)
+    }
+
+    /// Rebuilds the indexes. Needed after changing ids or names through `message_mut`.
+    /// When several messages share an id or a name, the first one is indexed.
+    pub fn reindex(&mut self) {
+        self.by_id.clear();
+        self.by_name.clear();
+        self.by_signal.clear();
+
+        for (index, message) in self.messages.iter().enumerate() {
+            if let Some(id) = message.id {
+                self.by_id.entry(id).or_insert(index);
+            }
+            for name in message
+                .name
+                .iter()
+                .chain(message.alt_names.iter().flatten())
+            {
+                self.by_name.entry(name.clone()).or_insert(index);
+            }
+            for (signal_index, (signal_name, signal)) in message.signals.iter().enumerate() {
+                for name in std::iter::once(signal_name).chain(signal.alt_names.iter().flatten()) {
+                    let entries = self.by_signal.entry(name.clone()).or_default();
+                    if !entries.contains(&(index, signal_index)) {
+                        entries.push((index, signal_index));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn messages(&self) -> &[CanMessage] {
+        &self.messages
+    }
+
+    pub fn message_mut(&mut self, index: usize) -> Option<&mut CanMessage> {
+        self.messages.get_mut(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn index_of_id(&self, id: CanId) -> Option<usize> {
+        self.by_id.get(&id).copied()
+    }
+
+    pub fn by_id(&self, id: CanId) -> Option<&CanMessage> {
+        self.index_of_id(id).map(|index| &self.messages[index])
+    }
+
+    /// Message matching a received frame.
+    pub fn by_frame_id(&self, raw: u32, extended: bool) -> Option<&CanMessage> {
+        let id = if extended {
+            CanId::Extended(raw)
+        } else {
+            CanId::from_raw(raw)?
+        };
+        self.by_id(id)
+            .filter(|message| message.id.is_some_and(|id| id.matches(raw, extended)))
+    }
+
+    pub fn index_of_name(&self, name: &str) -> Option<usize> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Message with this name or alternative name.
+    pub fn by_name(&self, name: &str) -> Option<&CanMessage> {
+        self.index_of_name(name).map(|index| &self.messages[index])
+    }
+
+    /// Every signal with this name or alternative name, with its message.
+    pub fn by_signal_name(&self, name: &str) -> Vec<(&CanMessage, &str, &Signal)> {
+        self.by_signal
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|&(index, signal_index)| {
+                let message = &self.messages[index];
+                let (signal_name, signal) = &message.signals[signal_index];
+                (message, signal_name.as_str(), signal)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database(texts: &[&str]) -> Database {
+        Database::new(
+            texts
+                .iter()
+                .map(|text| CanMessage::from_yaml_str(text, None).unwrap().0)
+                .collect(),
+        )
+    }
+
+    fn name(message: Option<&CanMessage>) -> Option<&str> {
+        message.and_then(|message| message.name.as_deref())
+    }
+
+    #[test]
+    fn messages_are_sorted_by_id() {
+        let database = database(&["id: 0x0F6\nname: B\n", "id: 0x036\nname: A\n"]);
+        assert_eq!(database.len(), 2);
+        assert_eq!(database.messages()[0].name.as_deref(), Some("A"));
+        assert_eq!(database.index_of_id(CanId::Standard(0x0F6)), Some(1));
+    }
+
+    #[test]
+    fn lookup_by_name_and_alt_name() {
+        let database = database(&[
+            "id: 0x0F6\nname: BSI_INFO_GEN\nalt_names: [DONNEES_BSI_LENTES, BSI_INFO]\n",
+            "id: 0x036\nname: COMMANDES_BSI\n",
+        ]);
+        assert_eq!(name(database.by_name("BSI_INFO_GEN")), Some("BSI_INFO_GEN"));
+        assert_eq!(name(database.by_name("BSI_INFO")), Some("BSI_INFO_GEN"));
+        assert_eq!(
+            name(database.by_name("COMMANDES_BSI")),
+            Some("COMMANDES_BSI")
+        );
+        assert_eq!(database.index_of_name("DONNEES_BSI_LENTES"), Some(1));
+        assert_eq!(name(database.by_name("bsi_info")), None);
+    }
+
+    #[test]
+    fn duplicated_id_resolves_to_the_first_message() {
+        let database = database(&[
+            "id: 0x0F6\nname: FIRST\n",
+            "id: 0x036\nname: OTHER\n",
+            "id: 0x0F6\nname: SECOND\n",
+        ]);
+        assert_eq!(name(database.by_id(CanId::Standard(0x0F6))), Some("FIRST"));
+        assert_eq!(name(database.by_name("SECOND")), Some("SECOND"));
+    }
+
+    #[test]
+    fn lookup_by_signal_name_and_alt_name() {
+        let database = database(&[
+            "id: 0x0F6
+name: BSI_INFO
+signals:
+  SPEED:
+    bits: \"1.7-1.0\"
+    alt_names: [VITESSE]
+",
+            "id: 0x036
+name: ABS_INFO
+signals:
+  VITESSE:
+    bits: \"2.7-2.0\"
+",
+        ]);
+        let found: Vec<(&str, &str)> = database
+            .by_signal_name("VITESSE")
+            .into_iter()
+            .map(|(message, signal_name, _)| (message.name.as_deref().unwrap(), signal_name))
+            .collect();
+        assert_eq!(found, [("ABS_INFO", "VITESSE"), ("BSI_INFO", "SPEED")]);
+        assert_eq!(database.by_signal_name("SPEED").len(), 1);
+        assert!(database.by_signal_name("RPM").is_empty());
+    }
+
+    #[test]
+    fn lookup_by_frame_id() {
+        let database = database(&["id: 0x036\nname: STD\n", "id: 0x18DAF110\nname: EXT\n"]);
+        assert_eq!(name(database.by_frame_id(0x036, false)), Some("STD"));
+        assert_eq!(name(database.by_frame_id(0x036, true)), None);
+        assert_eq!(name(database.by_frame_id(0x18DA_F110, true)), Some("EXT"));
+        assert_eq!(name(database.by_frame_id(0x18DA_F110, false)), None);
+        assert_eq!(name(database.by_frame_id(0x037, false)), None);
+    }
+}
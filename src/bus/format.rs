@@ -0,0 +1,44 @@
+pub mod csv;
+pub mod dbc;
+pub mod docs;
+pub mod dot;
+pub mod json;
+pub mod kcd;
+pub mod sym;
+
+use crate::bus::can::Translation;
+
+/// Turns a PSA-RE name into a C-like identifier, as most CAN tools require:
+/// characters other than letters, digits and '_' become '_'.
+pub fn identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// Escapes text for XML and HTML content and attribute values.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Double-quoted string for formats whose strings cannot contain double
+/// quotes, such as DBC and SYM: they become single quotes.
+pub fn quoted_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'"))
+}
+
+/// Label of an enum value in the first available language of `langs`, or
+/// the value itself when it has no label.
+pub fn value_label(value: i64, label: Option<&Translation>, langs: &[&str]) -> String {
+    label
+        .and_then(|label| label.resolve(langs))
+        .map_or(value.to_string(), String::from)
+}
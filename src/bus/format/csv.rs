@@ -0,0 +1,182 @@
+use crate::bus::can::CanMessage;
+
+/// Quotes a field when it holds a separator, a quote or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+/// Languages of the signal comments, those of `langs` first, then the
+/// others in alphabetical order.
+fn comment_languages<'a>(messages: &'a [CanMessage], langs: &[&'a str]) -> Vec<&'a str> {
+    let mut found: Vec<&str> = messages
+        .iter()
+        .flat_map(|message| &message.signals)
+        .filter_map(|(_, signal)| signal.comment.as_ref())
+        .flat_map(|comment| comment.languages())
+        .collect();
+    found.sort();
+    found.dedup();
+
+    let mut columns: Vec<&str> = langs
+        .iter()
+        .copied()
+        .filter(|lang| found.contains(lang))
+        .collect();
+    for lang in found {
+        if !columns.contains(&lang) {
+            columns.push(lang);
+        }
+    }
+    columns
+}
+
+/// Writes the signal matrix of a bus as RFC 4180 CSV, one row per signal,
+/// with a comment column per language. Senders and receivers are separated
+/// by spaces.
+pub fn to_csv(messages: &[CanMessage], langs: &[&str]) -> String {
+    let columns = comment_languages(messages, langs);
+
+    let mut header: Vec<String> = [
+        "message_id",
+        "message_name",
+        "signal_name",
+        "bits",
+        "type",
+        "signed",
+        "factor",
+        "offset",
+        "min",
+        "max",
+        "units",
+    ]
+    .map(String::from)
+    .to_vec();
+    header.extend(columns.iter().map(|lang| format!("comment_{}", lang)));
+    header.extend([String::from("senders"), String::from("receivers")]);
+
+    let mut csv = header.join(",");
+    csv.push_str("\r\n");
+    for message in messages {
+        for (name, signal) in &message.signals {
+            let bits = match &signal.bits {
+                Some(Ok(range)) => range.to_string(),
+                Some(Err(err)) => err.text().to_string(),
+                None => String::new(),
+            };
+            let mut row = vec![
+                optional(message.id),
+                optional(message.name.as_ref()),
+                name.clone(),
+                bits,
+                optional(signal.data_type.as_ref()),
+                signal.is_signed().to_string(),
+                optional(signal.factor),
+                optional(signal.offset),
+                optional(signal.min),
+                optional(signal.max),
+                optional(signal.units.as_ref()),
+            ];
+            row.extend(columns.iter().map(|lang| {
+                optional(
+                    signal
+                        .comment
+                        .as_ref()
+                        .and_then(|comment| comment.get(lang)),
+                )
+            }));
+            row.extend([message.senders.join(" "), message.receivers.join(" ")]);
+
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push_str("\r\n");
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<CanMessage> {
+        [
+            "id: 0x0F6
+name: BSI_INFO
+senders: [BSI]
+receivers: [CMB, BSM]
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    type: uint
+    factor: 0.5
+    offset: -40
+    units: \"°C\"
+    comment:
+      fr: \"Température, \\\"eau\\\"\"
+      en: \"Coolant\\ntemperature\"
+",
+            "id: 0x036
+name: COMMANDES_BSI
+signals:
+  MODE:
+    bits: \"1.1-1.0\"
+    signed: true
+    comment:
+      de: \"Modus\"
+",
+        ]
+        .iter()
+        .map(|text| CanMessage::from_yaml_str(text, None).unwrap().0)
+        .collect()
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(csv_field("BSI_INFO"), "BSI_INFO");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn rows_end_with_crlf_and_quote_fields() {
+        let csv = to_csv(&messages(), &["en"]);
+        let rows: Vec<&str> = csv.split_terminator("\r\n").collect();
+        assert_eq!(rows.len(), 3);
+        assert!(csv.ends_with("\r\n"));
+        assert_eq!(
+            rows[0],
+            "message_id,message_name,signal_name,bits,type,signed,factor,offset,min,max,units,\
+             comment_en,comment_de,comment_fr,senders,receivers"
+        );
+        assert_eq!(
+            rows[1],
+            "0x0F6,BSI_INFO,TEMP,2.7-2.0,uint,false,0.5,-40,,,°C,\"Coolant\ntemperature\",,\
+             \"Température, \"\"eau\"\"\",BSI,CMB BSM"
+        );
+        assert_eq!(
+            rows[2],
+            "0x036,COMMANDES_BSI,MODE,1.1-1.0,,true,,,,,,,Modus,,,"
+        );
+    }
+
+    #[test]
+    fn comment_columns_follow_langs_then_alphabetical_order() {
+        let messages = messages();
+        assert_eq!(comment_languages(&messages, &["fr"]), ["fr", "de", "en"]);
+        assert_eq!(
+            comment_languages(&messages, &["it", "en"]),
+            ["en", "de", "fr"]
+        );
+        assert_eq!(comment_languages(&messages[1..], &["en"]), ["de"]);
+    }
+}
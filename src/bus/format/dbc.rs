@@ -0,0 +1,934 @@
+use super::{identifier, quoted_string, value_label};
+use crate::bus::can::bits::{BitPosition, BitRange};
+use crate::bus::can::diagnostic::{Diagnostic, Severity};
+use crate::bus::can::id::{CanId, EXTENDED_MAX};
+use crate::bus::can::periodicity::Periodicity;
+use crate::bus::can::{CanMessage, Signal, Translation};
+use log::{debug, warn};
+
+/// Node used by DBC files when a message or signal has no sender or receiver.
+const NO_NODE: &str = "Vector__XXX";
+
+fn message_name(message: &CanMessage) -> String {
+    match (&message.name, message.id) {
+        (Some(name), _) => identifier(name),
+        (None, Some(id)) => format!("MSG_{:03X}", id.raw()),
+        (None, None) => String::from("MSG"),
+    }
+}
+
+fn node_list(nodes: &[String]) -> String {
+    if nodes.is_empty() {
+        String::from(NO_NODE)
+    } else {
+        nodes
+            .iter()
+            .map(|node| identifier(node))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Physical range of a signal: `min` and `max` when given, completed with the
+/// range of its raw values. Both zero, meaning unknown to DBC tools, when
+/// neither is given.
+fn physical_range(signal: &Signal, width: usize) -> (f64, f64) {
+    if signal.min.is_none() && signal.max.is_none() {
+        return (0.0, 0.0);
+    }
+    let (low, high) = signal.integer_range(width);
+    let (low, high) = (
+        signal.integer_to_physical(low),
+        signal.integer_to_physical(high),
+    );
+    (
+        signal.min.unwrap_or(low.min(high)),
+        signal.max.unwrap_or(low.max(high)),
+    )
+}
+
+/// Writes messages as a Vector DBC file. PSA-RE bit ranges are big-endian,
+/// so every signal is Motorola (`@0`) with its most significant bit as start
+/// bit. Comments and value labels are taken in the first available language
+/// of `langs`, and periodicities become `GenMsgCycleTime` attributes.
+/// Messages without id and signals without valid bits are left out.
+pub fn to_dbc(messages: &[CanMessage], langs: &[&str]) -> String {
+    let mut nodes: Vec<String> = Vec::new();
+    for message in messages {
+        for node in message.senders.iter().chain(&message.receivers) {
+            let node = identifier(node);
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+    }
+    nodes.sort();
+
+    let mut dbc = String::from("VERSION \"\"\n\n\n");
+    dbc.push_str("NS_ :\n");
+    for symbol in [
+        "NS_DESC_",
+        "CM_",
+        "BA_DEF_",
+        "BA_",
+        "VAL_",
+        "BA_DEF_DEF_",
+        "SIG_VALTYPE_",
+        "BO_TX_BU_",
+    ] {
+        dbc.push_str(&format!("\t{}\n", symbol));
+    }
+    dbc.push_str("\nBS_:\n\n");
+    dbc.push_str(&format!("BU_: {}\n\n", nodes.join(" ")));
+
+    let mut comments = Vec::new();
+    let mut attributes = Vec::new();
+    let mut value_tables = Vec::new();
+    let mut value_types = Vec::new();
+    let mut transmitters = Vec::new();
+
+    for message in messages {
+        let Some(id) = message.id else {
+            warn!(
+                "Skipping message {} without id in DBC export.",
+                message_name(message)
+            );
+            continue;
+        };
+        let dbc_id = if id.is_extended() {
+            id.raw() | 0x8000_0000
+        } else {
+            id.raw()
+        };
+
+        dbc.push_str(&format!(
+            "BO_ {} {}: {} {}\n",
+            dbc_id,
+            message_name(message),
+            message.length.unwrap_or(8),
+            message
+                .senders
+                .first()
+                .map_or(String::from(NO_NODE), |sender| identifier(sender))
+        ));
+        if message.senders.len() > 1 {
+            transmitters.push(format!(
+                "BO_TX_BU_ {} : {};",
+                dbc_id,
+                node_list(&message.senders)
+            ));
+        }
+        if let Some(comment) = message.comment.as_ref().and_then(|c| c.resolve(langs)) {
+            comments.push(format!("CM_ BO_ {} {};", dbc_id, quoted_string(comment)));
+        }
+        if let Some(period) = message.periodicity.as_ref().and_then(|p| p.period_ms()) {
+            attributes.push(format!(
+                "BA_ \"GenMsgCycleTime\" BO_ {} {};",
+                dbc_id, period
+            ));
+        }
+
+        for (name, signal) in &message.signals {
+            let Some(Ok(range)) = &signal.bits else {
+                warn!(
+                    "Skipping signal {} of {} without valid bits in DBC export.",
+                    name,
+                    message_name(message)
+                );
+                continue;
+            };
+            let signal_name = identifier(name);
+            let width = range.width();
+            let (min, max) = physical_range(signal, width);
+            dbc.push_str(&format!(
+                " SG_ {} : {}|{}@0{} ({},{}) [{}|{}] {} {}\n",
+                signal_name,
+                (range.start.byte as usize - 1) * 8 + range.start.bit as usize,
+                width,
+                if signal.is_signed() { '-' } else { '+' },
+                signal.factor.unwrap_or(1.0),
+                signal.offset.unwrap_or(0.0),
+                min,
+                max,
+                quoted_string(signal.units.as_deref().unwrap_or("")),
+                node_list(&message.receivers)
+            ));
+
+            if let Some(comment) = signal.comment.as_ref().and_then(|c| c.resolve(langs)) {
+                comments.push(format!(
+                    "CM_ SG_ {} {} {};",
+                    dbc_id,
+                    signal_name,
+                    quoted_string(comment)
+                ));
+            }
+            if !signal.values.is_empty() {
+                let mut table = format!("VAL_ {} {}", dbc_id, signal_name);
+                for (value, label) in &signal.values {
+                    let label = value_label(*value, label.as_ref(), langs);
+                    table.push_str(&format!(" {} {}", value, quoted_string(&label)));
+                }
+                table.push_str(" ;");
+                value_tables.push(table);
+            }
+            match (signal.data_type.as_deref(), width) {
+                (Some("float"), 32) => {
+                    value_types.push(format!("SIG_VALTYPE_ {} {} : 1;", dbc_id, signal_name))
+                }
+                (Some("float" | "double"), 64) => {
+                    value_types.push(format!("SIG_VALTYPE_ {} {} : 2;", dbc_id, signal_name))
+                }
+                _ => {}
+            }
+        }
+        dbc.push('\n');
+    }
+
+    for line in transmitters {
+        dbc.push_str(&line);
+        dbc.push('\n');
+    }
+    dbc.push('\n');
+    for line in comments {
+        dbc.push_str(&line);
+        dbc.push('\n');
+    }
+    dbc.push_str("BA_DEF_ BO_ \"GenMsgCycleTime\" INT 0 65535;\n");
+    dbc.push_str("BA_DEF_DEF_ \"GenMsgCycleTime\" 0;\n");
+    for line in attributes.iter().chain(&value_tables).chain(&value_types) {
+        dbc.push_str(line);
+        dbc.push('\n');
+    }
+    dbc
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Punct(char),
+}
+
+/// Splits a DBC file into tokens, each with its line number.
+fn tokenize(text: &str) -> Vec<(Token, usize)> {
+    let is_punct = |c: char| ":|@()[],;".contains(c);
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(&c) = chars.peek() {
+        if c == '\n' {
+            line += 1;
+            chars.next();
+        } else if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            let start_line = line;
+            chars.next();
+            let mut string = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        if let Some(escaped) = chars.next() {
+                            string.push(escaped);
+                        }
+                    }
+                    _ => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        string.push(c);
+                    }
+                }
+            }
+            tokens.push((Token::Text(string), start_line));
+        } else if is_punct(c) {
+            chars.next();
+            tokens.push((Token::Punct(c), line));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' || is_punct(c) {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push((Token::Word(word), line));
+        }
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(0, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position).map(|(token, _)| token);
+        self.position += 1;
+        token
+    }
+
+    fn word(&mut self) -> Option<String> {
+        match self.next() {
+            Some(Token::Word(word)) => Some(word.clone()),
+            _ => None,
+        }
+    }
+
+    fn text(&mut self) -> Option<String> {
+        match self.next() {
+            Some(Token::Text(text)) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Option<T> {
+        self.word()?.parse().ok()
+    }
+
+    fn punct(&mut self, expected: char) -> Option<()> {
+        match self.next() {
+            Some(Token::Punct(c)) if *c == expected => Some(()),
+            _ => None,
+        }
+    }
+
+    fn eat_punct(&mut self, expected: char) -> bool {
+        if self.peek() == Some(&Token::Punct(expected)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves past the end of the current statement: its ';' or the next
+    /// keyword at the start of a line.
+    fn skip_statement(&mut self, start_line: usize) {
+        while let Some((token, line)) = self.tokens.get(self.position) {
+            match token {
+                Token::Punct(';') => {
+                    self.position += 1;
+                    return;
+                }
+                Token::Word(word)
+                    if *line > start_line && word.ends_with('_') && word.len() > 2 =>
+                {
+                    return;
+                }
+                _ => self.position += 1,
+            }
+        }
+    }
+
+    /// Comma separated node names.
+    fn nodes(&mut self) -> Vec<String> {
+        let mut nodes = Vec::new();
+        while let Some(Token::Word(node)) = self.peek() {
+            nodes.push(node.clone());
+            self.position += 1;
+            if !self.eat_punct(',') {
+                break;
+            }
+        }
+        nodes.retain(|node| node != NO_NODE);
+        nodes
+    }
+}
+
+/// Signal as read from a `SG_` line.
+struct DbcSignal {
+    name: String,
+    /// Multiplexer indicator: "M" for the switch, "m<N>" for signals sent
+    /// when the switch has value N.
+    multiplexer: Option<String>,
+    start_bit: usize,
+    length: usize,
+    motorola: bool,
+    signed: bool,
+    factor: f64,
+    offset: f64,
+    min: f64,
+    max: f64,
+    units: String,
+    receivers: Vec<String>,
+}
+
+fn parse_signal(parser: &mut Parser) -> Option<DbcSignal> {
+    let name = parser.word()?;
+    // Multiplexer indicators come before the ':'
+    let multiplexer = match parser.peek() {
+        Some(Token::Word(indicator)) => {
+            let indicator = indicator.clone();
+            parser.next();
+            Some(indicator)
+        }
+        _ => None,
+    };
+    parser.punct(':')?;
+    let start_bit = parser.number()?;
+    parser.punct('|')?;
+    let length = parser.number()?;
+    parser.punct('@')?;
+    let order_and_sign = parser.word()?;
+    parser.punct('(')?;
+    let factor = parser.number()?;
+    parser.punct(',')?;
+    let offset = parser.number()?;
+    parser.punct(')')?;
+    parser.punct('[')?;
+    let min = parser.number()?;
+    parser.punct('|')?;
+    let max = parser.number()?;
+    parser.punct(']')?;
+    let units = parser.text()?;
+    let receivers = parser.nodes();
+
+    Some(DbcSignal {
+        name,
+        multiplexer,
+        start_bit,
+        length,
+        motorola: order_and_sign.starts_with('0'),
+        signed: order_and_sign.ends_with('-'),
+        factor,
+        offset,
+        min,
+        max,
+        units,
+        receivers,
+    })
+}
+
+/// Converts a DBC start bit and length to a PSA-RE bit range, or tells why
+/// it cannot be expressed. Intel signals can only be expressed when they
+/// stay within one byte.
+fn dbc_bits(signal: &DbcSignal) -> Result<BitRange, &'static str> {
+    if signal.length == 0 {
+        return Err("has a length of 0");
+    }
+    let byte = signal.start_bit / 8;
+    let bit = signal.start_bit % 8;
+    let start_index = if signal.motorola {
+        byte * 8 + (7 - bit)
+    } else if bit + signal.length <= 8 {
+        byte * 8 + (7 - (bit + signal.length - 1))
+    } else {
+        return Err("is little-endian across bytes");
+    };
+    let position = |index: usize| -> Result<BitPosition, &'static str> {
+        Ok(BitPosition {
+            byte: u8::try_from(index / 8 + 1).map_err(|_| "goes past byte 255")?,
+            bit: (7 - index % 8) as u8,
+        })
+    };
+    Ok(BitRange {
+        start: position(start_index)?,
+        end: position(start_index.saturating_add(signal.length - 1))?,
+    })
+}
+
+fn dbc_id(raw: u32) -> Option<CanId> {
+    if raw & 0x8000_0000 != 0 {
+        let raw = raw & !0x8000_0000;
+        (raw <= EXTENDED_MAX).then_some(CanId::Extended(raw))
+    } else {
+        CanId::from_raw(raw)
+    }
+}
+
+fn translation(lang: &str, text: &str) -> Translation {
+    let mut translation = Translation::default();
+    translation.set(lang, text);
+    translation
+}
+
+/// Builds PSA-RE messages from a DBC file. Comments and value labels are
+/// stored in `lang`. Receivers of the signals become receivers of their
+/// message, and `GenMsgCycleTime` its periodicity. Signals that PSA-RE bit
+/// ranges cannot express are left out and reported.
+pub fn from_dbc(
+    text: &str,
+    file_path: Option<&str>,
+    lang: &str,
+) -> (Vec<CanMessage>, Vec<Diagnostic>) {
+    let tokens = tokenize(text);
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+    };
+    let mut messages: Vec<(u32, CanMessage)> = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut report = |line: usize, message: String| {
+        let mut diagnostic = Diagnostic::new(Severity::Warning, "", message);
+        diagnostic.file_path = file_path.map(String::from);
+        diagnostic.line = Some(line);
+        diagnostics.push(diagnostic);
+    };
+
+    while let Some(token) = parser.peek() {
+        let line = parser.line();
+        let Token::Word(keyword) = token else {
+            parser.next();
+            continue;
+        };
+        let keyword = keyword.clone();
+        parser.next();
+        match keyword.as_str() {
+            "NS_" => {
+                // List of symbols, up to the "BS_" section
+                parser.eat_punct(':');
+                while let Some(Token::Word(word)) = parser.peek()
+                    && word != "BS_"
+                {
+                    parser.next();
+                }
+            }
+            "BO_" => {
+                let header = (|| {
+                    let raw: u32 = parser.number()?;
+                    let name = parser.word()?;
+                    parser.punct(':')?;
+                    let length: i64 = parser.number()?;
+                    let sender = parser.word()?;
+                    Some((raw, name, length, sender))
+                })();
+                let Some((raw, name, length, sender)) = header else {
+                    report(line, String::from("Invalid message definition."));
+                    parser.skip_statement(line);
+                    continue;
+                };
+                let id = dbc_id(raw);
+                if id.is_none() {
+                    debug!("Ignoring DBC message {} with id {:#X}.", name, raw);
+                }
+                messages.push((
+                    raw,
+                    CanMessage {
+                        id,
+                        name: Some(name),
+                        alt_names: None,
+                        length: Some(length),
+                        comment: None,
+                        bus_type: Some(String::from("can")),
+                        periodicity: None,
+                        senders: [sender].into_iter().filter(|s| s != NO_NODE).collect(),
+                        receivers: Vec::new(),
+                        signals: Vec::new(),
+                        extra: Vec::new(),
+                        source: None,
+                        file_path: None,
+                    },
+                ));
+            }
+            "SG_" => {
+                let Some((_, message)) = messages.last_mut() else {
+                    parser.skip_statement(line);
+                    continue;
+                };
+                let Some(dbc_signal) = parse_signal(&mut parser) else {
+                    report(line, String::from("Invalid signal definition."));
+                    parser.skip_statement(line);
+                    continue;
+                };
+                if let Some(indicator) = &dbc_signal.multiplexer
+                    && indicator != "M"
+                {
+                    report(
+                        line,
+                        format!(
+                            "Signal \"{}\" is multiplexed ({}), which PSA-RE messages cannot express.",
+                            dbc_signal.name, indicator
+                        ),
+                    );
+                    continue;
+                }
+                let bits = match dbc_bits(&dbc_signal) {
+                    Ok(bits) => bits,
+                    Err(reason) => {
+                        report(
+                            line,
+                            format!(
+                                "Signal \"{}\" {}, which PSA-RE bits cannot express.",
+                                dbc_signal.name, reason
+                            ),
+                        );
+                        continue;
+                    }
+                };
+                for receiver in &dbc_signal.receivers {
+                    if !message.receivers.contains(receiver) {
+                        message.receivers.push(receiver.clone());
+                    }
+                }
+                let has_range = dbc_signal.min != 0.0 || dbc_signal.max != 0.0;
+                message.signals.push((
+                    dbc_signal.name,
+                    Signal {
+                        alt_names: None,
+                        bits: Some(Ok(bits)),
+                        data_type: Some(String::from(
+                            match (dbc_signal.length, dbc_signal.signed) {
+                                (1, _) => "bool",
+                                (_, true) => "int",
+                                (_, false) => "uint",
+                            },
+                        )),
+                        signed: dbc_signal.signed.then_some(true),
+                        factor: (dbc_signal.factor != 1.0).then_some(dbc_signal.factor),
+                        offset: (dbc_signal.offset != 0.0).then_some(dbc_signal.offset),
+                        min: has_range.then_some(dbc_signal.min),
+                        max: has_range.then_some(dbc_signal.max),
+                        units: (!dbc_signal.units.is_empty()).then_some(dbc_signal.units),
+                        comment: None,
+                        values: Vec::new(),
+                        unused: None,
+                        extra: Vec::new(),
+                    },
+                ));
+            }
+            "BO_TX_BU_" => {
+                let raw: Option<u32> = parser.number();
+                parser.punct(':');
+                let senders = parser.nodes();
+                if let Some((_, message)) = messages.iter_mut().find(|(r, _)| Some(*r) == raw) {
+                    for sender in senders {
+                        if !message.senders.contains(&sender) {
+                            message.senders.push(sender);
+                        }
+                    }
+                }
+                parser.skip_statement(line);
+            }
+            "CM_" => {
+                let target = match parser.peek() {
+                    Some(Token::Word(kind)) if kind == "BO_" || kind == "SG_" => {
+                        let kind = kind.clone();
+                        parser.next();
+                        let raw: Option<u32> = parser.number();
+                        let signal = if kind == "SG_" { parser.word() } else { None };
+                        raw.map(|raw| (raw, signal))
+                    }
+                    _ => None,
+                };
+                if let Some((raw, signal)) = target
+                    && let Some(comment) = parser.text()
+                    && let Some((_, message)) = messages.iter_mut().find(|(r, _)| *r == raw)
+                {
+                    match signal {
+                        None => message.comment = Some(translation(lang, &comment)),
+                        Some(signal) => {
+                            if let Some((_, signal)) =
+                                message.signals.iter_mut().find(|(name, _)| *name == signal)
+                            {
+                                signal.comment = Some(translation(lang, &comment));
+                            }
+                        }
+                    }
+                }
+                parser.skip_statement(line);
+            }
+            "BA_" => {
+                if parser.text().as_deref() == Some("GenMsgCycleTime")
+                    && parser.word().as_deref() == Some("BO_")
+                    && let Some(raw) = parser.number::<u32>()
+                    && let Some(period) = parser.number::<f64>()
+                    && period > 0.0
+                    && let Some((_, message)) = messages.iter_mut().find(|(r, _)| *r == raw)
+                {
+                    message.periodicity = Some(Periodicity::from_integer(period as i64));
+                }
+                parser.skip_statement(line);
+            }
+            "VAL_" => {
+                let raw: Option<u32> = parser.number();
+                let signal_name = parser.word();
+                let mut values = Vec::new();
+                while let Some(Token::Word(_)) = parser.peek() {
+                    let value: Option<f64> = parser.number();
+                    let label = parser.text();
+                    if let (Some(value), Some(label)) = (value, label) {
+                        values.push((value as i64, Some(translation(lang, &label))));
+                    }
+                }
+                if let (Some(raw), Some(signal_name)) = (raw, signal_name)
+                    && let Some((_, message)) = messages.iter_mut().find(|(r, _)| *r == raw)
+                    && let Some((_, signal)) = message
+                        .signals
+                        .iter_mut()
+                        .find(|(name, _)| *name == signal_name)
+                {
+                    values.sort_by_key(|(value, _)| *value);
+                    signal.values = values;
+                    if signal.data_type.as_deref() != Some("bool") {
+                        signal.data_type = Some(String::from("enum"));
+                    }
+                }
+                parser.skip_statement(line);
+            }
+            "SIG_VALTYPE_" => {
+                let raw: Option<u32> = parser.number();
+                let signal_name = parser.word();
+                parser.punct(':');
+                let value_type: Option<u8> = parser.number();
+                if let (Some(raw), Some(signal_name), Some(1 | 2)) = (raw, signal_name, value_type)
+                    && let Some((_, message)) = messages.iter_mut().find(|(r, _)| *r == raw)
+                    && let Some((_, signal)) = message
+                        .signals
+                        .iter_mut()
+                        .find(|(name, _)| *name == signal_name)
+                {
+                    signal.data_type = Some(String::from("float"));
+                }
+                parser.skip_statement(line);
+            }
+            _ => parser.skip_statement(line),
+        }
+    }
+
+    let messages = messages
+        .into_iter()
+        .map(|(_, message)| message)
+        .filter(|message| message.id.is_some())
+        .collect();
+    (messages, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGES: [&str; 2] = [
+        "id: 0x0F6
+name: BSI_INFO
+length: 8
+comment:
+  en: \"General \\\"info\\\"\"
+periodicity: 100ms
+senders: [BSI]
+receivers: [CMB, BSM]
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    factor: 0.5
+    offset: -40
+    min: -40
+    max: 87.5
+    units: \"°C\"
+    comment:
+      en: \"Coolant\"
+  ODOMETER:
+    bits: \"3.7-5.0\"
+    type: uint
+  TURN:
+    bits: \"8.1-8.0\"
+    type: enum
+    values:
+      0:
+        en: \"off\"
+      3:
+        en: \"both\"
+",
+        "id: 0x18DAF110
+name: DIAG
+length: 4
+senders: [BSI]
+signals:
+  ACCEL:
+    bits: \"1.7-2.0\"
+    signed: true
+",
+    ];
+
+    fn messages() -> Vec<CanMessage> {
+        MESSAGES
+            .iter()
+            .map(|text| CanMessage::from_yaml_str(text, None).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn export_signals_as_motorola() {
+        let dbc = to_dbc(&messages(), &["en"]);
+        assert!(dbc.contains("BU_: BSI BSM CMB\n"));
+        assert!(dbc.contains("BO_ 246 BSI_INFO: 8 BSI\n"));
+        assert!(dbc.contains(" SG_ TEMP : 15|8@0+ (0.5,-40) [-40|87.5] \"°C\" CMB,BSM\n"));
+        assert!(dbc.contains(" SG_ ODOMETER : 23|24@0+ (1,0) [0|0] \"\" CMB,BSM\n"));
+        assert!(dbc.contains(" SG_ TURN : 57|2@0+ (1,0) [0|0] \"\" CMB,BSM\n"));
+    }
+
+    #[test]
+    fn export_extended_id() {
+        let dbc = to_dbc(&messages(), &["en"]);
+        assert!(dbc.contains(&format!(
+            "BO_ {} DIAG: 4 BSI\n",
+            0x18DAF110u32 | 0x8000_0000
+        )));
+        assert!(dbc.contains(" SG_ ACCEL : 7|16@0- (1,0) [0|0] \"\" Vector__XXX\n"));
+    }
+
+    #[test]
+    fn export_comments_values_and_cycle_time() {
+        let dbc = to_dbc(&messages(), &["en"]);
+        assert!(dbc.contains("CM_ BO_ 246 \"General 'info'\";\n"));
+        assert!(dbc.contains("CM_ SG_ 246 TEMP \"Coolant\";\n"));
+        assert!(dbc.contains("BA_ \"GenMsgCycleTime\" BO_ 246 100;\n"));
+        assert!(dbc.contains("VAL_ 246 TURN 0 \"off\" 3 \"both\" ;\n"));
+    }
+
+    fn import(text: &str) -> (Vec<CanMessage>, Vec<Diagnostic>) {
+        from_dbc(text, None, "en")
+    }
+
+    fn bits(message: &CanMessage, name: &str) -> String {
+        let (_, signal) = message.signals.iter().find(|(n, _)| n == name).unwrap();
+        match &signal.bits {
+            Some(Ok(range)) => range.to_string(),
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let original = messages();
+        let (imported, diagnostics) = import(&to_dbc(&original, &["en"]));
+        assert!(diagnostics.is_empty());
+        assert_eq!(imported.len(), original.len());
+
+        for (original, imported) in original.iter().zip(&imported) {
+            assert_eq!(imported.id, original.id);
+            assert_eq!(imported.name, original.name);
+            assert_eq!(imported.length, original.length);
+            assert_eq!(imported.senders, original.senders);
+            assert_eq!(imported.receivers, original.receivers);
+            assert_eq!(
+                imported.periodicity.as_ref().map(|p| p.to_psa_re()),
+                original.periodicity.as_ref().map(|p| p.to_psa_re())
+            );
+            assert_eq!(imported.signals.len(), original.signals.len());
+            for ((name, signal), (imported_name, imported_signal)) in
+                original.signals.iter().zip(&imported.signals)
+            {
+                assert_eq!(imported_name, name);
+                assert_eq!(bits(imported, name), bits(original, name));
+                assert_eq!(imported_signal.is_signed(), signal.is_signed());
+                assert_eq!(imported_signal.factor, signal.factor);
+                assert_eq!(imported_signal.offset, signal.offset);
+                assert_eq!(imported_signal.min, signal.min);
+                assert_eq!(imported_signal.max, signal.max);
+                assert_eq!(imported_signal.units, signal.units);
+                assert_eq!(imported_signal.values.len(), signal.values.len());
+            }
+        }
+        let comment = imported[0].comment.as_ref().and_then(|c| c.get("en"));
+        assert_eq!(comment, Some("General 'info'"));
+    }
+
+    #[test]
+    fn intel_signals_within_one_byte() {
+        let (messages, diagnostics) = import(
+            "BO_ 246 MSG: 8 BSI
+ SG_ LOW : 4|4@1+ (1,0) [0|0] \"\" CMB
+ SG_ FLAG : 0|1@1+ (1,0) [0|0] \"\" CMB
+ SG_ WIDE : 4|8@1+ (1,0) [0|0] \"\" CMB
+",
+        );
+        assert_eq!(bits(&messages[0], "LOW"), "1.7-1.4");
+        assert_eq!(bits(&messages[0], "FLAG"), "1.0");
+        assert_eq!(messages[0].signals.len(), 2);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .message
+                .contains("little-endian across bytes")
+        );
+    }
+
+    #[test]
+    fn extended_ids() {
+        let (messages, _) = import(
+            "BO_ 2564485392 EXT: 8 BSI
+BO_ 1960 STD: 8 BSI
+BO_ 3221225471 TOO_BIG: 8 BSI
+",
+        );
+        let ids: Vec<_> = messages.iter().map(|message| message.id).collect();
+        assert_eq!(
+            ids,
+            [Some(CanId::Extended(0x18DAF110)), CanId::from_raw(0x7A8)]
+        );
+    }
+
+    #[test]
+    fn comments_values_and_cycle_time() {
+        let (messages, diagnostics) = import(
+            "BO_ 246 MSG: 8 BSI
+ SG_ TURN : 57|2@0+ (1,0) [0|0] \"\" CMB
+
+CM_ BO_ 246 \"Lights
+and turn signals\";
+CM_ SG_ 246 TURN \"Turn signals\";
+BA_DEF_ BO_ \"GenMsgCycleTime\" INT 0 65535;
+BA_ \"GenMsgCycleTime\" BO_ 246 50;
+VAL_ 246 TURN 3 \"both\" 0 \"off\" ;
+",
+        );
+        assert!(diagnostics.is_empty());
+        let message = &messages[0];
+        let comment = message.comment.as_ref().and_then(|c| c.get("en"));
+        assert_eq!(comment, Some("Lights\nand turn signals"));
+        assert_eq!(
+            message
+                .periodicity
+                .as_ref()
+                .map(|p| p.to_psa_re())
+                .as_deref(),
+            Some("50ms")
+        );
+
+        let (_, turn) = &message.signals[0];
+        assert_eq!(
+            turn.comment.as_ref().and_then(|c| c.get("en")),
+            Some("Turn signals")
+        );
+        assert_eq!(turn.data_type.as_deref(), Some("enum"));
+        let values: Vec<_> = turn
+            .values
+            .iter()
+            .map(|(value, label)| (*value, label.as_ref().and_then(|l| l.get("en"))))
+            .collect();
+        assert_eq!(values, [(0, Some("off")), (3, Some("both"))]);
+    }
+
+    #[test]
+    fn multiplexed_signals_are_skipped() {
+        let (messages, diagnostics) = import(
+            "BO_ 246 MSG: 8 BSI
+ SG_ SWITCH M : 7|8@0+ (1,0) [0|0] \"\" CMB
+ SG_ A m0 : 15|8@0+ (1,0) [0|0] \"\" CMB
+ SG_ B m1 : 15|8@0+ (1,0) [0|0] \"\" CMB
+ SG_ HUGE : 7|5000@0+ (1,0) [0|0] \"\" CMB
+",
+        );
+        let names: Vec<_> = messages[0].signals.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["SWITCH"]);
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics[0].message.contains("multiplexed (m0)"));
+        assert!(diagnostics[2].message.contains("past byte 255"));
+    }
+}
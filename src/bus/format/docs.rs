@@ -0,0 +1,583 @@
+use super::{escape_xml, identifier};
+use crate::bus::can::ecu::EcuRegistry;
+use crate::bus::can::{CanMessage, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Html,
+    Markdown,
+}
+
+impl DocFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Html => "html",
+            DocFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Table cell, linking to another page when `link` is set.
+#[derive(Debug, Clone)]
+struct Cell {
+    text: String,
+    link: Option<String>,
+}
+
+/// Bits of a layout row taken by one signal, or unused when `signal` is `None`.
+#[derive(Debug, Clone)]
+struct Span {
+    signal: Option<usize>,
+    label: String,
+    bits: usize,
+}
+
+/// Part of a page, rendered the same way in every format.
+#[derive(Debug, Clone)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Table(Vec<String>, Vec<Vec<Cell>>),
+    /// One row per byte, bit 7 first.
+    Layout(Vec<Vec<Span>>),
+}
+
+fn cell(text: impl Into<String>) -> Cell {
+    Cell {
+        text: text.into(),
+        link: None,
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> Cell {
+    cell(value.map_or(String::new(), |value| value.to_string()))
+}
+
+/// Name of the page of a message, without extension.
+fn page_name(message: &CanMessage) -> String {
+    match (message.id, &message.name) {
+        (Some(id), _) if id.is_extended() => format!("{:08X}", id.raw()),
+        (Some(id), _) => format!("{:03X}", id.raw()),
+        (None, Some(name)) => identifier(name),
+        (None, None) => String::from("unknown"),
+    }
+}
+
+/// Page file name of every message. Messages sharing an id, which lint
+/// reports, get a numbered suffix after the first one.
+fn page_files(messages: &[CanMessage], format: DocFormat) -> Vec<String> {
+    let mut used = vec![String::from("index")];
+    messages
+        .iter()
+        .map(|message| {
+            let base = page_name(message);
+            let mut name = base.clone();
+            let mut number = 1;
+            while used.contains(&name) {
+                number += 1;
+                name = format!("{}_{}", base, number);
+            }
+            used.push(name.clone());
+            format!("{}.{}", name, format.extension())
+        })
+        .collect()
+}
+
+/// Signal covering each bit of the message, byte by byte, merged into spans.
+fn layout(message: &CanMessage) -> Vec<Vec<Span>> {
+    let last_byte = message
+        .signals
+        .iter()
+        .filter_map(|(_, signal)| match &signal.bits {
+            Some(Ok(range)) => Some(range.last_byte() as usize),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let length = (message.length.unwrap_or(0).max(0) as usize).max(last_byte);
+
+    let mut owners: Vec<Option<usize>> = vec![None; length * 8];
+    for (index, (_, signal)) in message.signals.iter().enumerate() {
+        if let Some(Ok(range)) = &signal.bits {
+            for position in range.positions() {
+                owners[position.msb_index()].get_or_insert(index);
+            }
+        }
+    }
+
+    owners
+        .chunks(8)
+        .map(|byte| {
+            let mut spans: Vec<Span> = Vec::new();
+            for owner in byte {
+                match spans.last_mut() {
+                    Some(span) if span.signal == *owner => span.bits += 1,
+                    _ => spans.push(Span {
+                        signal: *owner,
+                        label: owner
+                            .map_or(String::new(), |index| message.signals[index].0.clone()),
+                        bits: 1,
+                    }),
+                }
+            }
+            spans
+        })
+        .collect()
+}
+
+fn signal_row(name: &str, signal: &Signal, langs: &[&str]) -> Vec<Cell> {
+    let bits = match &signal.bits {
+        Some(Ok(range)) => range.to_string(),
+        Some(Err(err)) => format!("{} (invalid)", err.text()),
+        None => String::new(),
+    };
+    vec![
+        cell(name),
+        cell(bits),
+        optional(signal.data_type.as_ref()),
+        cell(if signal.is_signed() { "yes" } else { "" }),
+        optional(signal.factor),
+        optional(signal.offset),
+        optional(signal.min),
+        optional(signal.max),
+        optional(signal.units.as_ref()),
+        optional(signal.comment.as_ref().and_then(|c| c.resolve(langs))),
+    ]
+}
+
+fn message_blocks(message: &CanMessage, langs: &[&str]) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(1, message.label())];
+
+    let mut header = vec![
+        vec![cell("Id"), optional(message.id)],
+        vec![cell("Name"), optional(message.name.as_ref())],
+    ];
+    if let Some(alt_names) = &message.alt_names {
+        header.push(vec![cell("Alternative names"), cell(alt_names.join(", "))]);
+    }
+    header.extend([
+        vec![cell("Length"), optional(message.length)],
+        vec![
+            cell("Periodicity"),
+            optional(message.periodicity.as_ref().map(|p| p.to_psa_re())),
+        ],
+        vec![cell("Senders"), cell(message.senders.join(", "))],
+        vec![cell("Receivers"), cell(message.receivers.join(", "))],
+    ]);
+    blocks.push(Block::Table(
+        vec![String::from("Field"), String::from("Value")],
+        header,
+    ));
+
+    if let Some(comment) = message.comment.as_ref().and_then(|c| c.resolve(langs)) {
+        blocks.push(Block::Paragraph(comment.to_string()));
+    }
+
+    blocks.push(Block::Heading(2, String::from("Layout")));
+    blocks.push(Block::Layout(layout(message)));
+
+    blocks.push(Block::Heading(2, String::from("Signals")));
+    blocks.push(Block::Table(
+        [
+            "Signal", "Bits", "Type", "Signed", "Factor", "Offset", "Min", "Max", "Units",
+            "Comment",
+        ]
+        .map(String::from)
+        .to_vec(),
+        message
+            .signals
+            .iter()
+            .map(|(name, signal)| signal_row(name, signal, langs))
+            .collect(),
+    ));
+
+    for (name, signal) in &message.signals {
+        if signal.values.is_empty() {
+            continue;
+        }
+        blocks.push(Block::Heading(3, format!("{} values", name)));
+        blocks.push(Block::Table(
+            vec![String::from("Value"), String::from("Label")],
+            signal
+                .values
+                .iter()
+                .map(|(value, label)| {
+                    vec![
+                        cell(value.to_string()),
+                        optional(label.as_ref().and_then(|l| l.resolve(langs))),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+    blocks
+}
+
+fn index_blocks(
+    title: &str,
+    messages: &[CanMessage],
+    registry: &EcuRegistry,
+    files: &[String],
+) -> Vec<Block> {
+    let link = |index: usize, text: String| Cell {
+        text,
+        link: Some(files[index].clone()),
+    };
+    let mut blocks = vec![Block::Heading(1, title.to_string())];
+
+    blocks.push(Block::Heading(2, String::from("By id")));
+    blocks.push(Block::Table(
+        ["Id", "Name", "Length", "Periodicity", "Senders"]
+            .map(String::from)
+            .to_vec(),
+        messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| {
+                vec![
+                    link(
+                        index,
+                        message.id.map_or(String::from("?"), |id| id.to_string()),
+                    ),
+                    optional(message.name.as_ref()),
+                    optional(message.length),
+                    optional(message.periodicity.as_ref().map(|p| p.to_psa_re())),
+                    cell(message.senders.join(", ")),
+                ]
+            })
+            .collect(),
+    ));
+
+    let mut names: Vec<(&str, usize)> = messages
+        .iter()
+        .enumerate()
+        .flat_map(|(index, message)| {
+            message
+                .name
+                .iter()
+                .chain(message.alt_names.iter().flatten())
+                .map(move |name| (name.as_str(), index))
+        })
+        .collect();
+    names.sort_by(|a, b| a.0.cmp(b.0));
+    blocks.push(Block::Heading(2, String::from("By name")));
+    blocks.push(Block::Table(
+        vec![String::from("Name"), String::from("Message")],
+        names
+            .into_iter()
+            .map(|(name, index)| vec![cell(name), link(index, messages[index].label())])
+            .collect(),
+    ));
+
+    blocks.push(Block::Heading(2, String::from("By ECU")));
+    for ecu in registry.ecus() {
+        blocks.push(Block::Heading(3, ecu.name.clone()));
+        let rows = ecu
+            .transmits
+            .iter()
+            .map(|&index| ("sends", index))
+            .chain(ecu.receives.iter().map(|&index| ("receives", index)))
+            .filter_map(|(direction, index)| {
+                let message = messages.get(index)?;
+                Some(vec![cell(direction), link(index, message.label())])
+            })
+            .collect();
+        blocks.push(Block::Table(
+            vec![String::from("Direction"), String::from("Message")],
+            rows,
+        ));
+    }
+    blocks
+}
+
+/// Color of a signal in HTML layouts.
+fn signal_color(index: usize) -> &'static str {
+    const COLORS: [&str; 8] = [
+        "#cfe2f3", "#d9ead3", "#fff2cc", "#f4cccc", "#d9d2e9", "#fce5cd", "#d0e0e3", "#ead1dc",
+    ];
+    COLORS[index % COLORS.len()]
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let html_cell = |cell: &Cell| match &cell.link {
+        Some(link) => format!(
+            "<a href=\"{}\">{}</a>",
+            escape_xml(link),
+            escape_xml(&cell.text)
+        ),
+        None => escape_xml(&cell.text),
+    };
+
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_xml(title)));
+    html.push_str(
+        "<style>\n\
+         body { font-family: sans-serif; }\n\
+         table { border-collapse: collapse; margin-bottom: 1em; }\n\
+         th, td { border: 1px solid #999; padding: 2px 6px; }\n\
+         table.layout td { text-align: center; font-size: small; min-width: 4em; }\n\
+         </style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape_xml(text)));
+            }
+            Block::Paragraph(text) => {
+                html.push_str(&format!("<p>{}</p>\n", escape_xml(text)));
+            }
+            Block::Table(header, rows) => {
+                html.push_str("<table>\n<tr>");
+                for title in header {
+                    html.push_str(&format!("<th>{}</th>", escape_xml(title)));
+                }
+                html.push_str("</tr>\n");
+                for row in rows {
+                    html.push_str("<tr>");
+                    for cell in row {
+                        html.push_str(&format!("<td>{}</td>", html_cell(cell)));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+            Block::Layout(bytes) => {
+                html.push_str("<table class=\"layout\">\n<tr><th>Byte</th>");
+                for bit in (0..8).rev() {
+                    html.push_str(&format!("<th>{}</th>", bit));
+                }
+                html.push_str("</tr>\n");
+                for (byte, spans) in bytes.iter().enumerate() {
+                    html.push_str(&format!("<tr><th>{}</th>", byte + 1));
+                    for span in spans {
+                        let style = span.signal.map_or(String::new(), |index| {
+                            format!(" style=\"background: {}\"", signal_color(index))
+                        });
+                        html.push_str(&format!(
+                            "<td colspan=\"{}\"{}>{}</td>",
+                            span.bits,
+                            style,
+                            escape_xml(&span.label)
+                        ));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Text safe inside a Markdown heading, paragraph or table cell.
+fn markdown_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('#', "\\#")
+        .replace('<', "&lt;")
+        .replace('\n', "<br>")
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut markdown = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                markdown.push_str(&format!(
+                    "{} {}\n\n",
+                    "#".repeat(*level),
+                    markdown_text(text)
+                ));
+            }
+            Block::Paragraph(text) => {
+                markdown.push_str(&format!("{}\n\n", markdown_text(text)));
+            }
+            Block::Table(header, rows) => {
+                markdown.push_str(&markdown_row(
+                    &header
+                        .iter()
+                        .map(|title| markdown_text(title))
+                        .collect::<Vec<_>>(),
+                ));
+                markdown.push_str(&markdown_row(&vec![String::from("---"); header.len()]));
+                for row in rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|cell| match &cell.link {
+                            Some(link) => format!("[{}]({})", markdown_text(&cell.text), link),
+                            None => markdown_text(&cell.text),
+                        })
+                        .collect();
+                    markdown.push_str(&markdown_row(&cells));
+                }
+                markdown.push('\n');
+            }
+            Block::Layout(bytes) => {
+                // Markdown has no merged cells: the signal is repeated on each bit
+                let mut header = vec![String::from("Byte")];
+                header.extend((0..8).rev().map(|bit| bit.to_string()));
+                markdown.push_str(&markdown_row(&header));
+                markdown.push_str(&markdown_row(&vec![String::from("---"); header.len()]));
+                for (byte, spans) in bytes.iter().enumerate() {
+                    let mut cells = vec![(byte + 1).to_string()];
+                    for span in spans {
+                        for _ in 0..span.bits {
+                            cells.push(markdown_text(&span.label));
+                        }
+                    }
+                    markdown.push_str(&markdown_row(&cells));
+                }
+                markdown.push('\n');
+            }
+        }
+    }
+    markdown
+}
+
+fn render(title: &str, blocks: &[Block], format: DocFormat) -> String {
+    match format {
+        DocFormat::Html => render_html(title, blocks),
+        DocFormat::Markdown => render_markdown(blocks),
+    }
+}
+
+/// Documentation pages of a bus, as (file name, content): an index by id,
+/// name and ECU, and one page per message with its header, bit layout,
+/// signals and value labels. Texts are taken in the first available
+/// language of `langs`.
+pub fn pages(
+    title: &str,
+    messages: &[CanMessage],
+    registry: &EcuRegistry,
+    langs: &[&str],
+    format: DocFormat,
+) -> Vec<(String, String)> {
+    let files = page_files(messages, format);
+    let mut pages = vec![(
+        format!("index.{}", format.extension()),
+        render(
+            title,
+            &index_blocks(title, messages, registry, &files),
+            format,
+        ),
+    )];
+    for (message, file) in messages.iter().zip(files) {
+        pages.push((
+            file,
+            render(&message.label(), &message_blocks(message, langs), format),
+        ));
+    }
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> CanMessage {
+        CanMessage::from_yaml_str(text, None).unwrap().0
+    }
+
+    fn spans(bytes: &[Vec<Span>]) -> Vec<Vec<(Option<usize>, &str, usize)>> {
+        bytes
+            .iter()
+            .map(|spans| {
+                spans
+                    .iter()
+                    .map(|span| (span.signal, span.label.as_str(), span.bits))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn layout_merges_bits_into_spans() {
+        let message = message(
+            "id: 0x0F6
+length: 3
+signals:
+  MODE:
+    bits: \"1.7-1.4\"
+  SPEED:
+    bits: \"1.2-2.5\"
+",
+        );
+        assert_eq!(
+            spans(&layout(&message)),
+            [
+                vec![(Some(0), "MODE", 4), (None, "", 1), (Some(1), "SPEED", 3)],
+                vec![(Some(1), "SPEED", 3), (None, "", 5)],
+                vec![(None, "", 8)],
+            ]
+        );
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let messages = [message(
+            "id: 0x0F6
+name: A<B>
+comment:
+  en: \"Tom & \\\"Jerry\\\" <script>\"
+",
+        )];
+        let pages = pages(
+            "HS.IS",
+            &messages,
+            &EcuRegistry::default(),
+            &["en"],
+            DocFormat::Html,
+        );
+        let page = &pages[1].1;
+        assert!(page.contains("<title>0x0F6 A&lt;B&gt;</title>"));
+        assert!(page.contains("<p>Tom &amp; &quot;Jerry&quot; &lt;script&gt;</p>"));
+        assert!(!page.contains("<script>"));
+    }
+
+    #[test]
+    fn markdown_is_escaped() {
+        let messages = [message(
+            "id: 0x0F6
+name: INFO
+comment:
+  en: \"# not a heading | <b>\"
+",
+        )];
+        let pages = pages(
+            "HS.IS",
+            &messages,
+            &EcuRegistry::default(),
+            &["en"],
+            DocFormat::Markdown,
+        );
+        assert!(pages[1].1.contains("\n\\# not a heading \\| &lt;b>\n"));
+    }
+
+    #[test]
+    fn duplicate_ids_get_their_own_page() {
+        let messages = [
+            message("id: 0x0F6\nname: FIRST\n"),
+            message("id: 0x0F6\nname: SECOND\n"),
+            message("name: index\n"),
+        ];
+        let pages = pages(
+            "HS.IS",
+            &messages,
+            &EcuRegistry::default(),
+            &["en"],
+            DocFormat::Markdown,
+        );
+        let files: Vec<&str> = pages.iter().map(|(file, _)| file.as_str()).collect();
+        assert_eq!(files, ["index.md", "0F6.md", "0F6_2.md", "index_2.md"]);
+        assert!(pages[0].1.contains("| [0x0F6](0F6.md) | FIRST |"));
+        assert!(pages[0].1.contains("| [0x0F6](0F6_2.md) | SECOND |"));
+        assert!(pages[2].1.starts_with("# 0x0F6 SECOND\n"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::bus::can::CanMessage;
+use crate::bus::can::ecu::EcuRegistry;
+
+/// Node standing for a missing sender or receiver.
+const UNKNOWN_NODE: &str = "?";
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+/// Writes a Graphviz DOT graph with ECUs as nodes and messages as edges from
+/// their senders to their receivers. Messages sharing both ends are drawn as
+/// one edge. `ecu` keeps only the edges from or to this ECU, `message` only
+/// the message at this index.
+pub fn to_dot(
+    title: &str,
+    messages: &[CanMessage],
+    registry: &EcuRegistry,
+    ecu: Option<&str>,
+    message: Option<usize>,
+) -> String {
+    let ends = |message_index: usize, producers: bool| -> Vec<&str> {
+        let ecus = if producers {
+            registry.producers(message_index)
+        } else {
+            registry.consumers(message_index)
+        };
+        if ecus.is_empty() {
+            vec![UNKNOWN_NODE]
+        } else {
+            ecus.iter().map(|ecu| ecu.name.as_str()).collect()
+        }
+    };
+
+    let mut nodes: Vec<&str> = ecu.into_iter().collect();
+    let mut edges: Vec<(&str, &str, Vec<String>)> = Vec::new();
+    for (index, can_message) in messages.iter().enumerate() {
+        if message.is_some_and(|selected| selected != index) {
+            continue;
+        }
+        let senders = ends(index, true);
+        let receivers = ends(index, false);
+        for &sender in &senders {
+            for &receiver in &receivers {
+                if ecu.is_some_and(|ecu| ecu != sender && ecu != receiver) {
+                    continue;
+                }
+                for node in [sender, receiver] {
+                    if !nodes.contains(&node) {
+                        nodes.push(node);
+                    }
+                }
+                match edges
+                    .iter_mut()
+                    .find(|(from, to, _)| *from == sender && *to == receiver)
+                {
+                    Some((_, _, labels)) => labels.push(can_message.label()),
+                    None => edges.push((sender, receiver, vec![can_message.label()])),
+                }
+            }
+        }
+    }
+
+    let mut dot = format!("digraph {} {{\n", quoted(title));
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=box];\n");
+    for node in &nodes {
+        if *node == UNKNOWN_NODE {
+            dot.push_str(&format!(
+                "    {} [label=\"unknown\", style=dashed];\n",
+                quoted(node)
+            ));
+        } else {
+            dot.push_str(&format!("    {};\n", quoted(node)));
+        }
+    }
+    for (from, to, labels) in &edges {
+        let label = labels
+            .iter()
+            .map(|label| escape(label))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        dot.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            quoted(from),
+            quoted(to),
+            label
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<CanMessage> {
+        [
+            "id: 0x036\nname: COMMANDES_BSI\nsenders: [BSI]\nreceivers: [CMB]\n",
+            "id: 0x0F6\nname: BSI_INFO\nsenders: [BSI]\nreceivers: [CMB, ABS]\n",
+            "id: 0x128\nname: CMB_INFO\nreceivers: [BSI]\n",
+            "id: 0x168\nname: ABS_INFO\nsenders: [ABS]\n",
+        ]
+        .iter()
+        .map(|text| CanMessage::from_yaml_str(text, None).unwrap().0)
+        .collect()
+    }
+
+    #[test]
+    fn messages_sharing_ends_are_merged() {
+        let messages = messages();
+        let registry = EcuRegistry::new(&messages, &[]);
+        assert_eq!(
+            to_dot("HS.IS", &messages, &registry, None, None),
+            "digraph \"HS.IS\" {
+    rankdir=LR;
+    node [shape=box];
+    \"BSI\";
+    \"CMB\";
+    \"ABS\";
+    \"?\" [label=\"unknown\", style=dashed];
+    \"BSI\" -> \"CMB\" [label=\"0x036 COMMANDES_BSI\\n0x0F6 BSI_INFO\"];
+    \"BSI\" -> \"ABS\" [label=\"0x0F6 BSI_INFO\"];
+    \"?\" -> \"BSI\" [label=\"0x128 CMB_INFO\"];
+    \"ABS\" -> \"?\" [label=\"0x168 ABS_INFO\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn ecu_filter_keeps_its_edges() {
+        let messages = messages();
+        let registry = EcuRegistry::new(&messages, &[]);
+        let dot = to_dot("HS.IS", &messages, &registry, Some("ABS"), None);
+        let edges: Vec<&str> = dot.lines().filter(|line| line.contains("->")).collect();
+        assert_eq!(
+            edges,
+            [
+                "    \"BSI\" -> \"ABS\" [label=\"0x0F6 BSI_INFO\"];",
+                "    \"ABS\" -> \"?\" [label=\"0x168 ABS_INFO\"];",
+            ]
+        );
+        assert!(!dot.contains("\"CMB\""));
+    }
+
+    #[test]
+    fn message_filter_keeps_one_message() {
+        let messages = messages();
+        let registry = EcuRegistry::new(&messages, &[]);
+        let dot = to_dot("HS.IS", &messages, &registry, None, Some(1));
+        let edges: Vec<&str> = dot.lines().filter(|line| line.contains("->")).collect();
+        assert_eq!(
+            edges,
+            [
+                "    \"BSI\" -> \"ABS\" [label=\"0x0F6 BSI_INFO\"];",
+                "    \"BSI\" -> \"CMB\" [label=\"0x0F6 BSI_INFO\"];",
+            ]
+        );
+        assert!(!dot.contains("\"?\""));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::bus::can::{CanMessage, Signal, Translation};
+use crate::bus::repository::Bus;
+use std::fmt::Write;
+use yaml_rust2::Yaml;
+
+/// Version of the JSON schema, increased on incompatible changes.
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// JSON value, with object keys kept in insertion order so that the output
+/// is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl Json {
+    fn string(text: &str) -> Json {
+        Json::String(text.to_string())
+    }
+
+    fn strings(texts: &[String]) -> Json {
+        Json::Array(texts.iter().map(|text| Json::string(text)).collect())
+    }
+
+    fn or_null<T>(value: Option<T>, to_json: impl FnOnce(T) -> Json) -> Json {
+        value.map_or(Json::Null, to_json)
+    }
+
+    /// Writes the value on one line, or indented by two spaces per level when
+    /// `indent` is given.
+    fn write(&self, out: &mut String, indent: Option<usize>) {
+        let newline = |out: &mut String, level: usize| {
+            if indent.is_some() {
+                out.push('\n');
+                out.push_str(&"  ".repeat(level));
+            }
+        };
+        let level = indent.unwrap_or(0);
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(flag) => out.push_str(if *flag { "true" } else { "false" }),
+            Json::Integer(number) => {
+                let _ = write!(out, "{}", number);
+            }
+            Json::Number(number) if number.is_finite() => {
+                let _ = write!(out, "{}", number);
+            }
+            Json::Number(_) => out.push_str("null"),
+            Json::String(text) => write_string(out, text),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    newline(out, level + 1);
+                    item.write(out, indent.map(|level| level + 1));
+                }
+                newline(out, level);
+                out.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Object(fields) => {
+                out.push('{');
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    newline(out, level + 1);
+                    write_string(out, key);
+                    out.push(':');
+                    if indent.is_some() {
+                        out.push(' ');
+                    }
+                    value.write(out, indent.map(|level| level + 1));
+                }
+                newline(out, level);
+                out.push('}');
+            }
+        }
+    }
+
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        out.push('\n');
+        out
+    }
+
+    pub fn to_compact_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, None);
+        out
+    }
+}
+
+fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+fn yaml_to_json(yaml: &Yaml) -> Json {
+    match yaml {
+        Yaml::String(text) => Json::string(text),
+        Yaml::Integer(number) => Json::Integer(*number),
+        Yaml::Real(text) => text.parse().map_or(Json::string(text), Json::Number),
+        Yaml::Boolean(flag) => Json::Bool(*flag),
+        Yaml::Array(items) => Json::Array(items.iter().map(yaml_to_json).collect()),
+        Yaml::Hash(hash) => Json::Object(
+            hash.iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        Yaml::String(text) => text.clone(),
+                        Yaml::Integer(number) => number.to_string(),
+                        Yaml::Real(text) => text.clone(),
+                        Yaml::Boolean(flag) => flag.to_string(),
+                        _ => yaml_to_json(key).to_compact_string(),
+                    };
+                    (key, yaml_to_json(value))
+                })
+                .collect(),
+        ),
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => Json::Null,
+    }
+}
+
+fn extra_to_json(extra: &[(String, Yaml)]) -> Json {
+    Json::Object(
+        extra
+            .iter()
+            .map(|(key, value)| (key.clone(), yaml_to_json(value)))
+            .collect(),
+    )
+}
+
+impl Translation {
+    /// Object of texts by language.
+    pub fn to_json(&self) -> Json {
+        Json::Object(
+            self.iter()
+                .map(|(lang, text)| (lang.to_string(), Json::string(text)))
+                .collect(),
+        )
+    }
+}
+
+impl Signal {
+    pub fn to_json(&self, name: &str) -> Json {
+        let (bits, layout) = match &self.bits {
+            Some(Ok(range)) => (
+                Json::String(range.to_string()),
+                object(vec![
+                    ("start_byte", Json::Integer(range.start.byte as i64)),
+                    ("start_bit", Json::Integer(range.start.bit as i64)),
+                    ("end_byte", Json::Integer(range.end.byte as i64)),
+                    ("end_bit", Json::Integer(range.end.bit as i64)),
+                    ("length", Json::Integer(range.width() as i64)),
+                ]),
+            ),
+            Some(Err(err)) => (Json::string(err.text()), Json::Null),
+            None => (Json::Null, Json::Null),
+        };
+        object(vec![
+            ("name", Json::string(name)),
+            (
+                "alt_names",
+                Json::strings(self.alt_names.as_deref().unwrap_or_default()),
+            ),
+            ("bits", bits),
+            ("layout", layout),
+            (
+                "type",
+                Json::or_null(self.data_type.as_deref(), Json::string),
+            ),
+            ("signed", Json::or_null(self.signed, Json::Bool)),
+            ("factor", Json::or_null(self.factor, Json::Number)),
+            ("offset", Json::or_null(self.offset, Json::Number)),
+            ("min", Json::or_null(self.min, Json::Number)),
+            ("max", Json::or_null(self.max, Json::Number)),
+            ("units", Json::or_null(self.units.as_deref(), Json::string)),
+            (
+                "comment",
+                Json::or_null(self.comment.as_ref(), Translation::to_json),
+            ),
+            (
+                "values",
+                Json::Array(
+                    self.values
+                        .iter()
+                        .map(|(value, label)| {
+                            object(vec![
+                                ("value", Json::Integer(*value)),
+                                ("label", Json::or_null(label.as_ref(), Translation::to_json)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            ("unused", Json::or_null(self.unused, Json::Bool)),
+            ("extra", extra_to_json(&self.extra)),
+        ])
+    }
+}
+
+impl CanMessage {
+    /// The message in the schema documented in the README.
+    pub fn to_json(&self) -> Json {
+        object(self.json_fields())
+    }
+
+    fn json_fields(&self) -> Vec<(&str, Json)> {
+        vec![
+            (
+                "id",
+                Json::or_null(self.id, |id| Json::Integer(id.raw() as i64)),
+            ),
+            (
+                "id_hex",
+                Json::or_null(self.id, |id| Json::String(id.to_string())),
+            ),
+            (
+                "extended",
+                Json::Bool(self.id.is_some_and(|id| id.is_extended())),
+            ),
+            ("name", Json::or_null(self.name.as_deref(), Json::string)),
+            (
+                "alt_names",
+                Json::strings(self.alt_names.as_deref().unwrap_or_default()),
+            ),
+            ("length", Json::or_null(self.length, Json::Integer)),
+            (
+                "comment",
+                Json::or_null(self.comment.as_ref(), Translation::to_json),
+            ),
+            (
+                "type",
+                Json::or_null(self.bus_type.as_deref(), Json::string),
+            ),
+            (
+                "periodicity",
+                Json::or_null(self.periodicity.as_ref(), |periodicity| {
+                    object(vec![
+                        (
+                            "period_ms",
+                            Json::or_null(periodicity.period_ms(), |ms| Json::Integer(ms as i64)),
+                        ),
+                        ("triggered", Json::Bool(periodicity.is_triggered())),
+                        ("text", Json::String(periodicity.to_psa_re())),
+                    ])
+                }),
+            ),
+            ("senders", Json::strings(&self.senders)),
+            ("receivers", Json::strings(&self.receivers)),
+            (
+                "signals",
+                Json::Array(
+                    self.signals
+                        .iter()
+                        .map(|(name, signal)| signal.to_json(name))
+                        .collect(),
+                ),
+            ),
+            ("extra", extra_to_json(&self.extra)),
+            (
+                "file",
+                Json::or_null(self.file_path.as_deref(), Json::string),
+            ),
+        ]
+    }
+}
+
+/// Every bus as one JSON document.
+pub fn to_json(buses: &[Bus]) -> String {
+    object(vec![
+        ("schema_version", Json::Integer(SCHEMA_VERSION)),
+        (
+            "buses",
+            Json::Array(
+                buses
+                    .iter()
+                    .map(|bus| {
+                        object(vec![
+                            ("bus", Json::String(bus.name())),
+                            (
+                                "messages",
+                                Json::Array(
+                                    bus.database
+                                        .messages()
+                                        .iter()
+                                        .map(CanMessage::to_json)
+                                        .collect(),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+    .to_pretty_string()
+}
+
+/// One JSON message object per line, for every bus. Each object starts with
+/// `schema_version` and `bus`.
+pub fn to_json_lines(buses: &[Bus]) -> String {
+    let mut out = String::new();
+    for bus in buses {
+        for message in bus.database.messages() {
+            let mut fields = vec![
+                ("schema_version", Json::Integer(SCHEMA_VERSION)),
+                ("bus", Json::String(bus.name())),
+            ];
+            fields.extend(message.json_fields());
+            out.push_str(&object(fields).to_compact_string());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::can::ecu::EcuRegistry;
+    use crate::bus::database::Database;
+
+    fn message(text: &str) -> CanMessage {
+        CanMessage::from_yaml_str(text, None).unwrap().0
+    }
+
+    fn bus(network: &str, messages: Vec<CanMessage>) -> Bus {
+        Bus {
+            architecture: String::from("AEE2004"),
+            network: network.to_string(),
+            dir: String::new(),
+            ecus: EcuRegistry::new(&messages, &[]),
+            database: Database::new(messages),
+        }
+    }
+
+    #[test]
+    fn message_keys_in_order_with_nulls_and_empty_lists() {
+        let json = message(
+            "id: 0x036
+name: BSI_INFO
+signals:
+  SPEED:
+    bits: \"2.7-3.0\"
+",
+        )
+        .to_json()
+        .to_compact_string();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"id":54,"id_hex":"0x036","extended":false,"name":"BSI_INFO","alt_names":[],"#,
+                r#""length":null,"comment":null,"type":null,"periodicity":null,"#,
+                r#""senders":[],"receivers":[],"signals":[{"name":"SPEED","alt_names":[],"#,
+                r#""bits":"2.7-3.0","layout":{"start_byte":2,"start_bit":7,"end_byte":3,"#,
+                r#""end_bit":0,"length":16},"type":null,"signed":null,"factor":null,"#,
+                r#""offset":null,"min":null,"max":null,"units":null,"comment":null,"#,
+                r#""values":[],"unused":null,"extra":{}}],"extra":{},"file":null}"#
+            )
+        );
+    }
+
+    #[test]
+    fn extended_id_hex_has_eight_digits() {
+        let json = message("id: 0x18DAF110\nname: DIAG\n").to_json();
+        let Json::Object(fields) = json else {
+            panic!("message is not an object");
+        };
+        assert!(fields.contains(&(String::from("id"), Json::Integer(0x18DA_F110))));
+        assert!(fields.contains(&(String::from("id_hex"), Json::string("0x18DAF110"))));
+        assert!(fields.contains(&(String::from("extended"), Json::Bool(true))));
+
+        let json = message("id: 0x7FF\nname: DIAG\n").to_json();
+        let Json::Object(fields) = json else {
+            panic!("message is not an object");
+        };
+        assert!(fields.contains(&(String::from("id_hex"), Json::string("0x7FF"))));
+    }
+
+    #[test]
+    fn given_flags_and_invalid_bits() {
+        let json = message(
+            "id: 0x036
+signals:
+  ACCEL:
+    bits: \"1.8\"
+    signed: false
+    unused: true
+",
+        )
+        .to_json()
+        .to_compact_string();
+        assert!(json.contains(r#""bits":"1.8","layout":null,"#));
+        assert!(json.contains(r#""signed":false,"#));
+        assert!(json.contains(r#""unused":true,"#));
+    }
+
+    #[test]
+    fn non_string_keys_are_stringified() {
+        let json = message(
+            "id: 0x036
+custom:
+  1: one
+  ? [1, 2]
+  : pair
+  ? ~
+  : none
+",
+        )
+        .to_json()
+        .to_compact_string();
+        assert!(json.contains(r#""extra":{"custom":{"1":"one","[1,2]":"pair","null":"none"}}"#));
+    }
+
+    #[test]
+    fn document_and_lines_name_every_bus() {
+        let buses = [
+            bus("HS.IS", vec![message("id: 0x036\nname: A\n")]),
+            bus("CAN.CONF", vec![message("id: 0x0F6\nname: B\n")]),
+        ];
+
+        let document = to_json(&buses);
+        assert!(document.starts_with("{\n  \"schema_version\": 1,\n  \"buses\": [\n"));
+        assert!(document.contains("\"bus\": \"AEE2004/HS.IS\",\n      \"messages\": ["));
+        assert!(document.contains("\"bus\": \"AEE2004/CAN.CONF\""));
+
+        let lines = to_json_lines(&buses);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"schema_version":1,"bus":"AEE2004/HS.IS","id":54,"#));
+        assert!(lines[1].starts_with(r#"{"schema_version":1,"bus":"AEE2004/CAN.CONF","id":246,"#));
+    }
+}
@@ -0,0 +1,252 @@
+use super::{escape_xml, value_label};
+use crate::bus::can::ecu::EcuRegistry;
+use crate::bus::can::{CanMessage, Signal, Translation};
+use log::warn;
+
+/// Every language of a translation, one "lang: text" line each, so that no
+/// translation is lost.
+fn notes(translation: &Translation) -> String {
+    translation
+        .iter()
+        .map(|(lang, text)| format!("{}: {}", lang, text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn value_type(signal: &Signal, width: usize) -> &'static str {
+    match (signal.data_type.as_deref(), width) {
+        (Some("float"), 32) => "single",
+        (Some("float" | "double"), 64) => "double",
+        _ if signal.is_signed() => "signed",
+        _ => "unsigned",
+    }
+}
+
+/// Writes messages as a Kayak KCD network definition. ECUs become nodes,
+/// referenced as producers of messages and consumers of their signals.
+/// Notes keep the comments in every language, while value labels are taken
+/// in the first available language of `langs`. PSA-RE bit ranges are
+/// big-endian: `offset` is the index of the most significant bit, counted
+/// from bit 7 of the first byte, as read by cantools.
+pub fn to_kcd(
+    bus_name: &str,
+    messages: &[CanMessage],
+    registry: &EcuRegistry,
+    langs: &[&str],
+) -> String {
+    let node_id = |name: &str| {
+        registry
+            .ecus()
+            .iter()
+            .position(|ecu| ecu.name == name)
+            .map_or(0, |index| index + 1)
+    };
+
+    let mut kcd = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    kcd.push_str(
+        "<NetworkDefinition xmlns=\"http://kayak.2codeornot2code.org/1.0\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"Definition.xsd\">\n",
+    );
+    kcd.push_str(&format!(
+        "  <Document name=\"{}\">Generated from PSA-RE</Document>\n",
+        escape_xml(bus_name)
+    ));
+    for ecu in registry.ecus() {
+        kcd.push_str(&format!(
+            "  <Node id=\"{}\" name=\"{}\"/>\n",
+            node_id(&ecu.name),
+            escape_xml(&ecu.name)
+        ));
+    }
+    kcd.push_str(&format!("  <Bus name=\"{}\">\n", escape_xml(bus_name)));
+
+    for (index, message) in messages.iter().enumerate() {
+        let Some(id) = message.id else {
+            warn!("Skipping message without id in KCD export.");
+            continue;
+        };
+        let mut attributes = format!(
+            "id=\"0x{:03X}\" name=\"{}\" length=\"{}\"",
+            id.raw(),
+            escape_xml(message.name.as_deref().unwrap_or("")),
+            message.length.unwrap_or(8)
+        );
+        if let Some(periodicity) = &message.periodicity {
+            if let Some(period) = periodicity.period_ms() {
+                attributes.push_str(&format!(" interval=\"{}\"", period));
+            }
+            if periodicity.is_triggered() {
+                attributes.push_str(" triggered=\"true\"");
+            }
+        }
+        if id.is_extended() {
+            attributes.push_str(" format=\"extended\"");
+        }
+        kcd.push_str(&format!("    <Message {}>\n", attributes));
+        if let Some(comment) = &message.comment {
+            kcd.push_str(&format!(
+                "      <Notes>{}</Notes>\n",
+                escape_xml(&notes(comment))
+            ));
+        }
+        let producers = registry.producers(index);
+        if !producers.is_empty() {
+            kcd.push_str("      <Producer>\n");
+            for ecu in producers {
+                kcd.push_str(&format!(
+                    "        <NodeRef id=\"{}\"/>\n",
+                    node_id(&ecu.name)
+                ));
+            }
+            kcd.push_str("      </Producer>\n");
+        }
+        let consumers = registry.consumers(index);
+
+        for (name, signal) in &message.signals {
+            let Some(Ok(range)) = &signal.bits else {
+                warn!("Skipping signal {} without valid bits in KCD export.", name);
+                continue;
+            };
+            kcd.push_str(&format!(
+                "      <Signal name=\"{}\" offset=\"{}\" length=\"{}\" endianess=\"big\">\n",
+                escape_xml(name),
+                range.start.msb_index(),
+                range.width()
+            ));
+            if let Some(comment) = &signal.comment {
+                kcd.push_str(&format!(
+                    "        <Notes>{}</Notes>\n",
+                    escape_xml(&notes(comment))
+                ));
+            }
+            if !consumers.is_empty() {
+                kcd.push_str("        <Consumer>\n");
+                for ecu in &consumers {
+                    kcd.push_str(&format!(
+                        "          <NodeRef id=\"{}\"/>\n",
+                        node_id(&ecu.name)
+                    ));
+                }
+                kcd.push_str("        </Consumer>\n");
+            }
+
+            let mut value = format!(" type=\"{}\"", value_type(signal, range.width()));
+            for (attribute, number) in [
+                ("slope", signal.factor),
+                ("intercept", signal.offset),
+                ("min", signal.min),
+                ("max", signal.max),
+            ] {
+                if let Some(number) = number {
+                    value.push_str(&format!(" {}=\"{}\"", attribute, number));
+                }
+            }
+            if let Some(units) = &signal.units {
+                value.push_str(&format!(" unit=\"{}\"", escape_xml(units)));
+            }
+            kcd.push_str(&format!("        <Value{}/>\n", value));
+
+            if !signal.values.is_empty() {
+                kcd.push_str("        <LabelSet>\n");
+                for (value, label) in &signal.values {
+                    let label = value_label(*value, label.as_ref(), langs);
+                    kcd.push_str(&format!(
+                        "          <Label name=\"{}\" value=\"{}\"/>\n",
+                        escape_xml(&label),
+                        value
+                    ));
+                }
+                kcd.push_str("        </LabelSet>\n");
+            }
+            kcd.push_str("      </Signal>\n");
+        }
+        kcd.push_str("    </Message>\n");
+    }
+
+    kcd.push_str("  </Bus>\n");
+    kcd.push_str("</NetworkDefinition>\n");
+    kcd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_message_with_nodes_notes_and_labels() {
+        let messages = [CanMessage::from_yaml_str(
+            "id: 0x0F6
+name: BSI_INFO
+length: 8
+periodicity: 100ms
+comment:
+  en: \"General info\"
+  fr: \"Infos générales\"
+senders: [BSM, BSI]
+receivers: [CMB, ABS]
+signals:
+  TEMP:
+    bits: \"2.7-3.4\"
+    signed: true
+    factor: 0.5
+    offset: -40
+    units: \"°C\"
+    comment:
+      en: \"Coolant <temp>\"
+  TURN:
+    bits: \"8.1-8.0\"
+    values:
+      0:
+        en: \"off\"
+      3:
+",
+            None,
+        )
+        .unwrap()
+        .0];
+        let registry = EcuRegistry::new(&messages, &[]);
+        assert_eq!(
+            to_kcd("HS.IS", &messages, &registry, &["en"]),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<NetworkDefinition xmlns=\"http://kayak.2codeornot2code.org/1.0\" \
+xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:schemaLocation=\"Definition.xsd\">
+  <Document name=\"HS.IS\">Generated from PSA-RE</Document>
+  <Node id=\"1\" name=\"ABS\"/>
+  <Node id=\"2\" name=\"BSI\"/>
+  <Node id=\"3\" name=\"BSM\"/>
+  <Node id=\"4\" name=\"CMB\"/>
+  <Bus name=\"HS.IS\">
+    <Message id=\"0x0F6\" name=\"BSI_INFO\" length=\"8\" interval=\"100\">
+      <Notes>en: General info
+fr: Infos générales</Notes>
+      <Producer>
+        <NodeRef id=\"2\"/>
+        <NodeRef id=\"3\"/>
+      </Producer>
+      <Signal name=\"TEMP\" offset=\"8\" length=\"12\" endianess=\"big\">
+        <Notes>en: Coolant &lt;temp&gt;</Notes>
+        <Consumer>
+          <NodeRef id=\"1\"/>
+          <NodeRef id=\"4\"/>
+        </Consumer>
+        <Value type=\"signed\" slope=\"0.5\" intercept=\"-40\" unit=\"°C\"/>
+      </Signal>
+      <Signal name=\"TURN\" offset=\"62\" length=\"2\" endianess=\"big\">
+        <Consumer>
+          <NodeRef id=\"1\"/>
+          <NodeRef id=\"4\"/>
+        </Consumer>
+        <Value type=\"unsigned\"/>
+        <LabelSet>
+          <Label name=\"off\" value=\"0\"/>
+          <Label name=\"3\" value=\"3\"/>
+        </LabelSet>
+      </Signal>
+    </Message>
+  </Bus>
+</NetworkDefinition>
+"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use super::{identifier, quoted_string, value_label};
+use crate::bus::can::{CanMessage, Signal};
+use log::warn;
+
+fn variable_type(signal: &Signal, width: usize) -> &'static str {
+    match (signal.data_type.as_deref(), width) {
+        (Some("float"), 32) => "float",
+        (Some("float" | "double"), 64) => "double",
+        (_, 1) if !signal.is_signed() => "bit",
+        _ if signal.is_signed() => "signed",
+        _ => "unsigned",
+    }
+}
+
+/// Writes messages as a PCAN symbol file. Value labels, in the first
+/// available language of `langs`, become enums shared by signals with the
+/// same name and values. PSA-RE bit ranges are big-endian, so every variable
+/// is Motorola (`-m`) with the index of its most significant bit, counted
+/// from bit 7 of the first byte, as start bit. Comments follow their message
+/// header or variable as `//` comments.
+pub fn to_sym(title: &str, messages: &[CanMessage], langs: &[&str]) -> String {
+    // Enum name and definition of every signal with value labels
+    let mut enums: Vec<(String, String)> = Vec::new();
+    let mut enum_of = |message_name: &str, signal_name: &str, signal: &Signal| -> String {
+        let values = signal
+            .values
+            .iter()
+            .map(|(value, label)| {
+                let label = value_label(*value, label.as_ref(), langs);
+                format!("{}={}", value, quoted_string(&label))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut name = identifier(signal_name);
+        if let Some((existing, existing_values)) = enums.iter().find(|(n, _)| *n == name) {
+            if *existing_values == values {
+                return existing.clone();
+            }
+            name = identifier(&format!("{}_{}", message_name, signal_name));
+        }
+        enums.push((name.clone(), values));
+        name
+    };
+
+    let mut sections = String::new();
+    for message in messages {
+        let Some(id) = message.id else {
+            warn!("Skipping message without id in SYM export.");
+            continue;
+        };
+        let message_name = match &message.name {
+            Some(name) => identifier(name),
+            None => format!("MSG_{:03X}", id.raw()),
+        };
+
+        sections.push_str(&format!("\n[{}]\n", message_name));
+        if id.is_extended() {
+            sections.push_str("Type=Extended\n");
+            sections.push_str(&format!("ID={:08X}h\n", id.raw()));
+        } else {
+            sections.push_str(&format!("ID={:03X}h\n", id.raw()));
+        }
+        sections.push_str(&format!("Len={}\n", message.length.unwrap_or(8)));
+        if let Some(period) = message.periodicity.as_ref().and_then(|p| p.period_ms()) {
+            sections.push_str(&format!("CycleTime={}\n", period));
+        }
+        if let Some(comment) = message.comment.as_ref().and_then(|c| c.resolve(langs)) {
+            sections.push_str(&format!("// {}\n", comment.replace('\n', " ")));
+        }
+
+        for (name, signal) in &message.signals {
+            let Some(Ok(range)) = &signal.bits else {
+                warn!("Skipping signal {} without valid bits in SYM export.", name);
+                continue;
+            };
+            let width = range.width();
+            let mut line = format!(
+                "Var={} {} {},{} -m",
+                identifier(name),
+                variable_type(signal, width),
+                range.start.msb_index(),
+                width
+            );
+            if let Some(units) = &signal.units {
+                line.push_str(&format!(" /u:{}", quoted_string(units)));
+            }
+            if let Some(factor) = signal.factor {
+                line.push_str(&format!(" /f:{}", factor));
+            }
+            if let Some(offset) = signal.offset {
+                line.push_str(&format!(" /o:{}", offset));
+            }
+            if let Some(min) = signal.min {
+                line.push_str(&format!(" /min:{}", min));
+            }
+            if let Some(max) = signal.max {
+                line.push_str(&format!(" /max:{}", max));
+            }
+            if !signal.values.is_empty() {
+                line.push_str(&format!(" /e:{}", enum_of(&message_name, name, signal)));
+            }
+            if let Some(comment) = signal.comment.as_ref().and_then(|c| c.resolve(langs)) {
+                line.push_str(&format!(" // {}", comment.replace('\n', " ")));
+            }
+            sections.push_str(&line);
+            sections.push('\n');
+        }
+    }
+
+    let mut sym = String::from("FormatVersion=6.0 // Do not edit this line!\n");
+    sym.push_str(&format!("Title={}\n", quoted_string(title)));
+    if !enums.is_empty() {
+        sym.push_str("\n{ENUMS}\n");
+        for (name, values) in &enums {
+            sym.push_str(&format!("enum {}({})\n", name, values));
+        }
+    }
+    sym.push_str("\n{SENDRECEIVE}\n");
+    sym.push_str(&sections);
+    sym
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_message_with_enum_signed_and_range() {
+        let message = CanMessage::from_yaml_str(
+            "id: 0x0F6
+name: BSI_INFO
+length: 8
+periodicity: 100ms
+comment:
+  en: \"General info\"
+signals:
+  TEMP:
+    bits: \"2.7-2.0\"
+    signed: true
+    factor: 0.5
+    offset: -40
+    min: -40
+    max: 87.5
+    units: \"°C\"
+    comment:
+      en: \"Coolant \\\"water\\\" temperature\"
+  TURN:
+    bits: \"8.1-8.0\"
+    type: enum
+    values:
+      0:
+        en: \"off\"
+      3:
+        en: \"both\"
+",
+            None,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(
+            to_sym("HS.IS", &[message], &["en"]),
+            "FormatVersion=6.0 // Do not edit this line!
+Title=\"HS.IS\"
+
+{ENUMS}
+enum TURN(0=\"off\", 3=\"both\")
+
+{SENDRECEIVE}
+
+[BSI_INFO]
+ID=0F6h
+Len=8
+CycleTime=100
+// General info
+Var=TEMP signed 8,8 -m /u:\"°C\" /f:0.5 /o:-40 /min:-40 /max:87.5 // Coolant \"water\" temperature
+Var=TURN unsigned 62,2 -m /e:TURN
+"
+        );
+    }
+}
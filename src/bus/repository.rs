@@ -0,0 +1,137 @@
+use crate::bus::can::diagnostic::Diagnostic;
+use crate::bus::can::ecu::{Ecu, EcuRegistry};
+use crate::bus::database::{Database, is_message_file};
+use log::{debug, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Network of one architecture, e.g. HS.IS of AEE2004.full, with its messages.
+#[derive(Debug, Clone)]
+pub struct Bus {
+    pub architecture: String,
+    pub network: String,
+    pub dir: String,
+    pub database: Database,
+    /// ECUs of the bus, built when it is loaded.
+    pub ecus: EcuRegistry,
+}
+
+impl Bus {
+    /// Name shown in tabs and accepted by `--bus`, e.g. `AEE2004.full/HS.IS`.
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.architecture, self.network)
+    }
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map_or(String::new(), |name| name.to_string_lossy().to_string())
+}
+
+fn sorted_subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+    subdirs
+}
+
+fn has_message_files(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|entry| is_message_file(&entry.path()))
+}
+
+/// Finds the bus directories below `root`, as (architecture, network, directory).
+/// `root` may be a PSA-RE checkout, its `buses` directory, an architecture
+/// directory or a single bus directory.
+pub fn discover(root: &str) -> Vec<(String, String, PathBuf)> {
+    let root = Path::new(root);
+    let buses_dir = root.join("buses");
+    if !buses_dir.is_dir() && has_message_files(root) {
+        let architecture = root.parent().map(dir_name).unwrap_or_default();
+        return vec![(architecture, dir_name(root), root.to_path_buf())];
+    }
+
+    let base = if buses_dir.is_dir() { &buses_dir } else { root };
+    let mut found = Vec::new();
+    for dir in sorted_subdirs(base) {
+        if has_message_files(&dir) {
+            found.push((dir_name(base), dir_name(&dir), dir));
+            continue;
+        }
+        for network_dir in sorted_subdirs(&dir) {
+            if has_message_files(&network_dir) {
+                found.push((dir_name(&dir), dir_name(&network_dir), network_dir));
+            }
+        }
+    }
+    found
+}
+
+/// Loads every bus found below `root`, each into its own database, with the
+/// registry of its ECUs built from `ecus` and the message senders and receivers.
+pub fn load_buses(root: &str, ecus: &[Ecu]) -> (Vec<Bus>, Vec<Diagnostic>) {
+    let mut buses = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (architecture, network, dir) in discover(root) {
+        let dir = dir.to_string_lossy().to_string();
+        debug!("Loading bus {}/{} from {}.", architecture, network, dir);
+        let (database, bus_diagnostics) = Database::load_dir(&dir);
+        diagnostics.extend(bus_diagnostics);
+        let ecus = EcuRegistry::new(database.messages(), ecus);
+        buses.push(Bus {
+            architecture,
+            network,
+            dir,
+            database,
+            ecus,
+        });
+    }
+
+    if buses.is_empty() {
+        let message = match fs::read_dir(root) {
+            Ok(_) => String::from("No bus directory with message files found."),
+            Err(err) => format!("Failed to read directory: {}.", err),
+        };
+        diagnostics.push(Diagnostic::directory(root, message));
+    }
+    (buses, diagnostics)
+}
+
+/// Loads the ECU description files of the `ecus` directory of a PSA-RE
+/// checkout. Having none is not a problem.
+pub fn load_ecus(root: &str) -> (Vec<Ecu>, Vec<Diagnostic>) {
+    let mut ecus = Vec::new();
+    let mut diagnostics = Vec::new();
+    let Ok(dir_entries) = fs::read_dir(Path::new(root).join("ecus")) else {
+        return (ecus, diagnostics);
+    };
+
+    let mut paths: Vec<PathBuf> = dir_entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        if is_message_file(&path)
+            && let Some(file_path) = path.to_str()
+        {
+            debug!("Loading ECU description {}.", file_path);
+            match Ecu::from_yaml_file(file_path) {
+                Ok((ecu, ecu_diagnostics)) => {
+                    ecus.push(ecu);
+                    diagnostics.extend(ecu_diagnostics);
+                }
+                Err(diagnostic) => {
+                    warn!("Skipping {}: {}", file_path, diagnostic);
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+    }
+    (ecus, diagnostics)
+}
@@ -0,0 +1,623 @@
+use crate::app;
+use crate::bus::can;
+use crate::bus::can::coverage;
+use crate::bus::can::diagnostic::Severity;
+use crate::bus::can::ecu;
+use crate::bus::can::encode::SignalValue;
+use crate::bus::can::id::CanId;
+use crate::bus::can::lint;
+use crate::bus::compare;
+use crate::bus::format::{csv, dbc, docs, dot, json, kcd, sym};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Runs a headless command. `--bus <name>` before the command selects the
+/// bus it applies to, the first one by default.
+pub fn run(app: &mut app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = match args {
+        [flag, name, rest @ ..] if flag == "--bus" => {
+            if !app.select_bus(name) {
+                return Err(format!("No bus named \"{}\".", name).into());
+            }
+            rest
+        }
+        _ => args,
+    };
+    let Some(command) = args.first() else {
+        return Err("Missing command.".into());
+    };
+
+    match command.as_str() {
+        "buses" => buses(app),
+        "compare" => compare(app, &args[1..]),
+        "csv" => csv(app, &args[1..]),
+        "dbc" => dbc(app, &args[1..]),
+        "docs" => docs(app, &args[1..]),
+        "dot" => dot(app, &args[1..]),
+        "import-dbc" => import_dbc(&args[1..]),
+        "json" => json(app, &args[1..]),
+        "kcd" => kcd(app, &args[1..]),
+        "sym" => sym(app, &args[1..]),
+        "ecus" => ecus(app),
+        "ecu" => ecu(app, &args[1..]),
+        "consumers" => consumers(app, &args[1..]),
+        "decode" => decode(app, &args[1..]),
+        "encode" => encode(app, &args[1..]),
+        "yaml" => yaml(app, &args[1..]),
+        "find" => find(app, &args[1..]),
+        "problems" => problems(app),
+        "lint" => lint(app, &args[1..]),
+        "translations" => translations(app, &args[1..]),
+        cmd => Err(format!("Unknown command \"{}\".", cmd).into()),
+    }
+}
+
+fn parse_payload(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex digit '{}' in payload.", c).into());
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err("Payload must contain an even number of hex digits.".into());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|e| e.into()))
+        .collect()
+}
+
+/// Index in the selected bus of the message with this name, alternative name or id.
+fn find_message_index(app: &app::App, key: &str) -> Result<usize, Box<dyn Error>> {
+    let database = app.database();
+    database
+        .index_of_name(key)
+        .or_else(|| CanId::parse(key).and_then(|id| database.index_of_id(id)))
+        .ok_or_else(|| format!("No CAN message with name or id \"{}\".", key).into())
+}
+
+fn find_message<'a>(app: &'a app::App, key: &str) -> Result<&'a can::CanMessage, Box<dyn Error>> {
+    Ok(&app.database().messages()[find_message_index(app, key)?])
+}
+
+/// `decode <id> <payload>`: prints every signal of a frame.
+fn decode(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [id, payload @ ..] = args else {
+        return Err("Usage: decode <id> <hex payload>".into());
+    };
+    let message = find_message(app, id)?;
+    let payload = parse_payload(&payload.join(""))?;
+
+    for result in message.decode(&payload, &app.app_config.language_chain()) {
+        match result {
+            Ok(signal) => {
+                let mut line = format!("{} = {}", signal.name, signal.value);
+                if let Some(units) = &signal.units {
+                    line.push_str(&format!(" {}", units));
+                }
+                line.push_str(&format!(" (raw 0x{:X})", signal.raw));
+                if let Some(label) = &signal.label {
+                    line.push_str(&format!(" \"{}\"", label));
+                }
+                println!("{}", line);
+            }
+            Err(err) => println!("{}", err),
+        }
+    }
+    Ok(())
+}
+
+/// `encode <id> <SIGNAL=value>...`: prints the payload built from signal values.
+/// Values that are not numbers are looked up in the signal value labels.
+fn encode(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [id, assignments @ ..] = args else {
+        return Err("Usage: encode <id> <SIGNAL=value>...".into());
+    };
+    let message = find_message(app, id)?;
+
+    let mut values = HashMap::new();
+    for assignment in assignments {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or(format!("Expected SIGNAL=value, got \"{}\".", assignment))?;
+        let value = match value.parse::<f64>() {
+            Ok(number) => SignalValue::Physical(number),
+            Err(_) => SignalValue::Label(value.to_string()),
+        };
+        values.insert(name.to_string(), value);
+    }
+
+    let payload = message.encode(&values)?;
+    let hex: Vec<String> = payload.iter().map(|byte| format!("{:02X}", byte)).collect();
+    println!("{}", hex.join(" "));
+    Ok(())
+}
+
+/// `yaml <id>`: prints the message serialized in PSA-RE format.
+fn yaml(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [id] = args else {
+        return Err("Usage: yaml <id>".into());
+    };
+    print!("{}", find_message(app, id)?.to_yaml_string());
+    Ok(())
+}
+
+/// `buses`: lists the buses found in `database_dir`.
+fn buses(app: &app::App) -> Result<(), Box<dyn Error>> {
+    for bus in &app.buses {
+        println!(
+            "{: <24} {: >4} message(s)  {}",
+            bus.name(),
+            bus.database.len(),
+            bus.dir
+        );
+    }
+    Ok(())
+}
+
+/// `compare <bus>`: matches the messages of the selected bus with those of
+/// another bus by name and prints their differences.
+fn compare(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [other] = args else {
+        return Err("Usage: compare <bus>".into());
+    };
+    let other_index = app
+        .find_bus(other)
+        .ok_or_else(|| format!("No bus named \"{}\".", other))?;
+    let left_name = app
+        .buses
+        .get(app.active_bus)
+        .map_or(String::new(), |bus| bus.name());
+    let right_name = app.buses[other_index].name();
+
+    let comparisons = compare::compare(app.database(), &app.buses[other_index].database);
+    for comparison in &comparisons {
+        match (comparison.left, comparison.right) {
+            (Some(left), Some(right)) => {
+                if comparison.differences.is_empty() {
+                    continue;
+                }
+                println!("{} <-> {}", left.label(), right.label());
+                for difference in &comparison.differences {
+                    println!(
+                        "  {}: {} -> {}",
+                        difference.field,
+                        difference.left.as_deref().unwrap_or("-"),
+                        difference.right.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+            (Some(left), None) => println!("only in {}: {}", left_name, left.label()),
+            (None, Some(right)) => println!("only in {}: {}", right_name, right.label()),
+            (None, None) => {}
+        }
+    }
+    println!(
+        "{} message(s) compared, {} identical.",
+        comparisons.len(),
+        comparisons.iter().filter(|c| c.is_identical()).count()
+    );
+    Ok(())
+}
+
+/// `ecus`: lists the ECUs of the selected bus and the messages nobody sends.
+fn ecus(app: &app::App) -> Result<(), Box<dyn Error>> {
+    let messages = app.database().messages();
+    for ecu in app.ecu_registry().ecus() {
+        println!(
+            "{: <20} {: >4} sent {: >4} received{}",
+            ecu.name,
+            ecu.transmits.len(),
+            ecu.receives.len(),
+            if ecu.file_path.is_none() {
+                "  (no description)"
+            } else {
+                ""
+            }
+        );
+    }
+    let unsent = ecu::unsent_messages(messages);
+    if !unsent.is_empty() {
+        println!("Messages without sender:");
+        for index in unsent {
+            println!("  {}", messages[index].label());
+        }
+    }
+    Ok(())
+}
+
+/// `ecu <name>`: lists the messages an ECU sends and receives.
+fn ecu(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [name] = args else {
+        return Err("Usage: ecu <name>".into());
+    };
+    let registry = app.ecu_registry();
+    let ecu = registry
+        .get(name)
+        .ok_or_else(|| format!("No ECU named \"{}\" on this bus.", name))?;
+    let messages = app.database().messages();
+
+    println!("{}", ecu.name);
+    if let Some(comment) = ecu
+        .comment
+        .as_ref()
+        .and_then(|c| c.resolve(&app.app_config.language_chain()))
+    {
+        println!("{}", comment);
+    }
+    for (title, indexes) in [("Transmits", &ecu.transmits), ("Receives", &ecu.receives)] {
+        println!("{} ({}):", title, indexes.len());
+        for &index in indexes {
+            println!("  {}", messages[index].label());
+        }
+    }
+    Ok(())
+}
+
+/// `consumers <id>`: lists the ECUs sending and receiving a message.
+fn consumers(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [id] = args else {
+        return Err("Usage: consumers <id>".into());
+    };
+    let index = find_message_index(app, id)?;
+    let message = &app.database().messages()[index];
+    let registry = app.ecu_registry();
+    let names = |ecus: Vec<&ecu::Ecu>| {
+        ecus.iter()
+            .map(|ecu| ecu.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    println!("{}", message.label());
+    println!("Senders: {}", names(registry.producers(index)));
+    println!("Receivers: {}", names(registry.consumers(index)));
+    Ok(())
+}
+
+/// Name of the selected bus, for exported files.
+fn bus_name(app: &app::App) -> String {
+    app.buses
+        .get(app.active_bus)
+        .map_or(String::from("bus"), |bus| bus.name())
+}
+
+/// Languages to export texts in: `lang` if given, then the configured chain.
+fn export_languages<'a>(app: &'a app::App, lang: Option<&'a str>) -> Vec<&'a str> {
+    let mut langs: Vec<&str> = lang.into_iter().collect();
+    for configured in app.app_config.language_chain() {
+        if !langs.contains(&configured) {
+            langs.push(configured);
+        }
+    }
+    langs
+}
+
+/// `csv`: prints the signals of the selected bus as CSV, with a comment
+/// column per language.
+fn csv(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    if !args.is_empty() {
+        return Err("Usage: csv".into());
+    }
+    print!(
+        "{}",
+        csv::to_csv(app.database().messages(), &app.app_config.language_chain())
+    );
+    Ok(())
+}
+
+/// `dbc [lang]`: prints the selected bus as a Vector DBC file, with comments
+/// and value labels in `lang`, `default_lang` by default.
+fn dbc(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let lang = match args {
+        [] => None,
+        [lang] => Some(lang.as_str()),
+        _ => return Err("Usage: dbc [lang]".into()),
+    };
+    print!(
+        "{}",
+        dbc::to_dbc(app.database().messages(), &export_languages(app, lang))
+    );
+    Ok(())
+}
+
+/// `json [--lines]`: prints every bus as a JSON document, or one JSON
+/// message per line.
+fn json(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args {
+        [] => print!("{}", json::to_json(&app.buses)),
+        [flag] if flag == "--lines" => print!("{}", json::to_json_lines(&app.buses)),
+        _ => return Err("Usage: json [--lines]".into()),
+    }
+    Ok(())
+}
+
+/// `kcd [lang]`: prints the selected bus as a Kayak KCD file, with value
+/// labels in `lang`, `default_lang` by default.
+fn kcd(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let lang = match args {
+        [] => None,
+        [lang] => Some(lang.as_str()),
+        _ => return Err("Usage: kcd [lang]".into()),
+    };
+    print!(
+        "{}",
+        kcd::to_kcd(
+            &bus_name(app),
+            app.database().messages(),
+            app.ecu_registry(),
+            &export_languages(app, lang)
+        )
+    );
+    Ok(())
+}
+
+/// `sym [lang]`: prints the selected bus as a PCAN symbol file, with value
+/// labels in `lang`, `default_lang` by default.
+fn sym(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let lang = match args {
+        [] => None,
+        [lang] => Some(lang.as_str()),
+        _ => return Err("Usage: sym [lang]".into()),
+    };
+    print!(
+        "{}",
+        sym::to_sym(
+            &bus_name(app),
+            app.database().messages(),
+            &export_languages(app, lang)
+        )
+    );
+    Ok(())
+}
+
+/// `import-dbc <file> <dir> [lang]`: writes the messages of a DBC file to
+/// `dir` as PSA-RE message files, with comments and value labels in `lang`,
+/// "en" by default. Existing files are left untouched.
+fn import_dbc(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (file, dir, lang) = match args {
+        [file, dir] => (file, dir, "en"),
+        [file, dir, lang] => (file, dir, lang.as_str()),
+        _ => return Err("Usage: import-dbc <file> <dir> [lang]".into()),
+    };
+    // DBC files are often in Windows-1252, read as Latin-1 when not UTF-8
+    let text = match String::from_utf8(fs::read(file)?) {
+        Ok(text) => text,
+        Err(err) => err.into_bytes().iter().map(|&byte| byte as char).collect(),
+    };
+
+    let (messages, diagnostics) = dbc::from_dbc(&text, Some(file), lang);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    fs::create_dir_all(dir)?;
+    let mut written = 0;
+    for message in &messages {
+        let Some(id) = message.id else {
+            continue;
+        };
+        let digits = if id.is_extended() {
+            format!("{:08X}", id.raw())
+        } else {
+            format!("{:03X}", id.raw())
+        };
+        let path = Path::new(dir).join(format!("{}.yml", digits));
+        if path.exists() {
+            println!("{}: already exists, skipped.", path.display());
+            continue;
+        }
+        fs::write(&path, message.to_yaml_string())?;
+        written += 1;
+    }
+    println!(
+        "{} message(s) read, {} file(s) written, {} problem(s).",
+        messages.len(),
+        written,
+        diagnostics.len()
+    );
+    Ok(())
+}
+
+/// `docs <dir> [--markdown] [lang]`: writes HTML, or Markdown, documentation
+/// of the selected bus to `dir`, with texts in `lang`, `default_lang` by default.
+fn docs(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let Some((dir, options)) = args.split_first() else {
+        return Err("Usage: docs <dir> [--markdown] [lang]".into());
+    };
+    let mut format = docs::DocFormat::Html;
+    let mut lang = None;
+    for option in options {
+        match option.as_str() {
+            "--markdown" => format = docs::DocFormat::Markdown,
+            option if lang.is_none() && !option.starts_with('-') => lang = Some(option),
+            _ => return Err("Usage: docs <dir> [--markdown] [lang]".into()),
+        }
+    }
+
+    let pages = docs::pages(
+        &bus_name(app),
+        app.database().messages(),
+        app.ecu_registry(),
+        &export_languages(app, lang),
+        format,
+    );
+    fs::create_dir_all(dir)?;
+    for (file_name, content) in &pages {
+        fs::write(Path::new(dir).join(file_name), content)?;
+    }
+    println!("{} page(s) written to {}.", pages.len(), dir);
+    Ok(())
+}
+
+/// `dot [--ecu <name>] [--message <id>]`: prints the ECUs and messages of
+/// the selected bus as a Graphviz graph.
+fn dot(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let registry = app.ecu_registry();
+    let mut ecu = None;
+    let mut message = None;
+    for option in args.chunks(2) {
+        match option {
+            [flag, name] if flag == "--ecu" => {
+                let found = registry
+                    .get(name)
+                    .ok_or_else(|| format!("No ECU named \"{}\" on this bus.", name))?;
+                ecu = Some(found.name.as_str());
+            }
+            [flag, id] if flag == "--message" => message = Some(find_message_index(app, id)?),
+            _ => return Err("Usage: dot [--ecu <name>] [--message <id>]".into()),
+        }
+    }
+
+    print!(
+        "{}",
+        dot::to_dot(
+            &bus_name(app),
+            app.database().messages(),
+            registry,
+            ecu,
+            message
+        )
+    );
+    Ok(())
+}
+
+/// `find <name>`: lists the messages and signals with this name or
+/// alternative name, in every bus.
+fn find(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [name] = args else {
+        return Err("Usage: find <name>".into());
+    };
+
+    let mut found = false;
+    for bus in &app.buses {
+        if let Some(message) = bus.database.by_name(name) {
+            println!("{}: message {}", bus.name(), message.label());
+            found = true;
+        }
+        for (message, signal_name, signal) in bus.database.by_signal_name(name) {
+            let bits = match &signal.bits {
+                Some(Ok(range)) => range.to_string(),
+                _ => String::from("?"),
+            };
+            println!(
+                "{}: signal {} in {} ({})",
+                bus.name(),
+                signal_name,
+                message.label(),
+                bits
+            );
+            found = true;
+        }
+    }
+    if !found {
+        return Err(format!("No message or signal named \"{}\".", name).into());
+    }
+    Ok(())
+}
+
+/// `problems`: lists the problems found while loading the database.
+fn problems(app: &app::App) -> Result<(), Box<dyn Error>> {
+    for diagnostic in &app.diagnostics {
+        println!("{}", diagnostic);
+    }
+    println!(
+        "{} message(s) loaded in {} bus(es), {} file(s) failed, {} problem(s).",
+        app.buses
+            .iter()
+            .map(|bus| bus.database.len())
+            .sum::<usize>(),
+        app.buses.len(),
+        app.failed_file_count(),
+        app.diagnostics.len()
+    );
+    Ok(())
+}
+
+/// `lint [dir]`: checks every bus, or the buses of another directory, and
+/// fails if any error is found. Duplicates are looked for within each bus.
+fn lint(app: &mut app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    if let [dir] = args {
+        app.app_config.database_dir = dir.clone();
+        app.load_can_messages();
+    }
+
+    let languages = app.app_config.language_chain();
+    let mut diagnostics = app.diagnostics.clone();
+    for bus in &app.buses {
+        diagnostics.extend(lint::lint(bus.database.messages(), &languages));
+    }
+    diagnostics.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    println!(
+        "{} message(s) checked, {} error(s), {} warning(s).",
+        app.buses
+            .iter()
+            .map(|bus| bus.database.len())
+            .sum::<usize>(),
+        errors,
+        diagnostics.len() - errors
+    );
+    if errors > 0 {
+        return Err(format!("{} error(s) found.", errors).into());
+    }
+    Ok(())
+}
+
+/// `translations [lang]`: prints the translation coverage per file and the
+/// texts missing in a language, `default_lang` by default.
+fn translations(app: &app::App, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let lang = match args {
+        [] => app.app_config.default_lang.as_str(),
+        [lang] => lang.as_str(),
+        _ => return Err("Usage: translations [lang]".into()),
+    };
+
+    let files = coverage::coverage(app.database().messages(), lang);
+    println!("Coverage for \"{}\":", lang);
+    for file in &files {
+        println!(
+            "{: >6.1}% {: >4}/{: <4} {}",
+            file.percent(),
+            file.translated,
+            file.total,
+            file.file_path
+        );
+    }
+
+    let reference_langs: Vec<&str> = app
+        .app_config
+        .language_chain()
+        .into_iter()
+        .filter(|l| *l != lang)
+        .collect();
+    let missing = coverage::missing_translations(app.database().messages(), lang, &reference_langs);
+    println!("Missing:");
+    for item in &missing {
+        let message = &app.database().messages()[item.message_index];
+        println!(
+            "{}: {}: \"{}\"",
+            message.file_path.as_deref().unwrap_or("?"),
+            item.location.field_path(),
+            item.reference.as_deref().unwrap_or("")
+        );
+    }
+
+    let (translated, total) = files
+        .iter()
+        .fold((0, 0), |(t, n), file| (t + file.translated, n + file.total));
+    let percent = if total == 0 {
+        100.0
+    } else {
+        translated as f64 * 100.0 / total as f64
+    };
+    println!("Total: {}/{} ({:.1}%).", translated, total, percent);
+    Ok(())
+}
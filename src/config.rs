@@ -1,17 +1,21 @@
 use log::{debug, warn};
-use std::{fs, io::Write};
+use std::{fs, io};
 use yaml_rust2::{Yaml, YamlEmitter, YamlLoader, yaml};
 
 pub struct Config {
+    /// PSA-RE checkout, or one architecture or bus directory of it.
     pub database_dir: String,
     pub default_lang: String,
+    /// Languages tried in order when a text is missing in `default_lang`.
+    pub fallback_langs: Vec<String>,
 }
 
 impl Config {
     fn get_default_config() -> Config {
         Config {
-            database_dir: String::from("../PSA-RE/buses/AEE2004.full/HS.IS/"),
+            database_dir: String::from("../PSA-RE/"),
             default_lang: String::from("en"),
+            fallback_langs: vec![String::from("en"), String::from("fr")],
         }
     }
 
@@ -40,6 +44,16 @@ impl Config {
                                 warn!("[WARNING] Wrong type for \"default_lang\".");
                             }
                         }
+                        "fallback_langs" => {
+                            if let Yaml::Array(arr) = value {
+                                config.fallback_langs = arr
+                                    .iter()
+                                    .filter_map(|item| item.as_str().map(String::from))
+                                    .collect();
+                            } else {
+                                warn!("[WARNING] Wrong type for \"fallback_langs\".");
+                            }
+                        }
                         _ => {
                             warn!("[WARNING] Unknown configuration parameter \"{}\".", k);
                         }
@@ -50,28 +64,43 @@ impl Config {
         config
     }
 
-    fn save_config(file_path: &str, config: Config) -> Config {
+    pub fn save_config(&self, file_path: &str) -> io::Result<()> {
         let mut hash = yaml::Hash::new();
         hash.insert(
             Yaml::String("database_dir".into()),
-            Yaml::String(config.database_dir.clone()),
+            Yaml::String(self.database_dir.clone()),
         );
         hash.insert(
             Yaml::String("default_lang".into()),
-            Yaml::String(config.default_lang.clone()),
+            Yaml::String(self.default_lang.clone()),
+        );
+        hash.insert(
+            Yaml::String("fallback_langs".into()),
+            Yaml::Array(
+                self.fallback_langs
+                    .iter()
+                    .map(|lang| Yaml::String(lang.clone()))
+                    .collect(),
+            ),
         );
 
         let yaml_doc = Yaml::Hash(hash);
         let mut file_str = String::new();
         YamlEmitter::new(&mut file_str)
             .dump(&yaml_doc)
-            .expect("Failed to emit YAML.");
+            .map_err(io::Error::other)?;
+        fs::write(file_path, file_str)
+    }
 
-        let mut new_config_file = fs::File::create(file_path).unwrap();
-        new_config_file
-            .write_all(file_str.as_bytes())
-            .expect("Failed to write to file.");
-        config
+    /// Languages in which texts are looked up, `default_lang` first.
+    pub fn language_chain(&self) -> Vec<&str> {
+        let mut chain = vec![self.default_lang.as_str()];
+        for lang in &self.fallback_langs {
+            if !chain.contains(&lang.as_str()) {
+                chain.push(lang);
+            }
+        }
+        chain
     }
 
     pub fn load_config_file(file_path: &str) -> Config {
@@ -79,12 +108,13 @@ impl Config {
             debug!("Loading configuration.");
             let yaml_content =
                 fs::read_to_string(file_path).expect("Unable to open configuration file.");
-            let config = Self::load_config_str(&yaml_content);
-            config
+            Self::load_config_str(&yaml_content)
         } else {
             let config = Self::get_default_config();
             debug!("Config file not found. Creating default one.");
-            let config = Self::save_config(file_path, config);
+            if let Err(err) = config.save_config(file_path) {
+                warn!("Failed to create configuration file {}: {}", file_path, err);
+            }
             config
         }
     }
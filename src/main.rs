@@ -4,14 +4,24 @@ use ratatui::crossterm::event;
 use ratatui::crossterm::execute;
 use ratatui::crossterm::terminal;
 use ratatui::prelude;
+use std::env;
 use std::io;
 
 mod app;
 pub mod bus;
+mod cli;
 pub mod config;
 mod ui;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Headless commands
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        let mut app = app::App::new();
+        app.load_can_messages();
+        return cli::run(&mut app, &args);
+    }
+
     // Setup terminal
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -51,7 +61,7 @@ fn run_app<B: prelude::Backend>(
     app: &mut app::App,
 ) -> io::Result<bool> {
     loop {
-        terminal.draw(|f| ui::ui(f, &app))?;
+        terminal.draw(|f| ui::ui(f, app))?;
 
         if let event::Event::Key(key) = event::read()? {
             // dbg!(key.code)
@@ -71,15 +81,130 @@ fn run_app<B: prelude::Backend>(
                         app.edit_window = Some(app::EditWindow::EditCanMsg);
                         app.active_screen = app::ActiveScreen::Editing;
                     }
+                    event::KeyCode::Char('p') => {
+                        app.active_screen = app::ActiveScreen::Problems;
+                    }
+                    event::KeyCode::Char('l') => {
+                        app.cycle_language();
+                    }
+                    event::KeyCode::Char('t') => {
+                        app.selected_missing = 0;
+                        app.active_screen = app::ActiveScreen::Translations;
+                    }
+                    event::KeyCode::Char('c') => {
+                        app.open_comparison();
+                    }
+                    event::KeyCode::Char('u') => {
+                        app.selected_ecu = 0;
+                        app.active_screen = app::ActiveScreen::Ecus;
+                    }
+                    event::KeyCode::Tab => {
+                        app.select_next_bus();
+                    }
+                    event::KeyCode::BackTab => {
+                        app.select_previous_bus();
+                    }
+                    event::KeyCode::Down => {
+                        app.select_next_message();
+                    }
+                    event::KeyCode::Up => {
+                        app.select_previous_message();
+                    }
                     _ => {}
                 },
-                app::ActiveScreen::Editing => match key.code {
+                app::ActiveScreen::Editing => match app.edit_window {
+                    Some(app::EditWindow::Translation) => match key.code {
+                        event::KeyCode::Enter => {
+                            app.save_translation();
+                            app.edit_window = None;
+                            app.active_screen = app::ActiveScreen::Translations;
+                        }
+                        event::KeyCode::Esc => {
+                            app.input.clear();
+                            app.edit_window = None;
+                            app.active_screen = app::ActiveScreen::Translations;
+                        }
+                        event::KeyCode::Backspace => {
+                            app.input.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.input.push(c);
+                        }
+                        _ => {}
+                    },
+                    _ => {
+                        if let event::KeyCode::Char('q') = key.code {
+                            return Ok(false);
+                        }
+                    }
+                },
+                app::ActiveScreen::Problems => match key.code {
                     event::KeyCode::Char('q') => {
-                        return Ok(false);
+                        return Ok(true);
+                    }
+                    event::KeyCode::Char('p') | event::KeyCode::Esc => {
+                        app.active_screen = app::ActiveScreen::CanBus;
+                    }
+                    _ => {}
+                },
+                app::ActiveScreen::Compare => match key.code {
+                    event::KeyCode::Char('q') => {
+                        return Ok(true);
+                    }
+                    event::KeyCode::Char('c') | event::KeyCode::Esc => {
+                        app.active_screen = app::ActiveScreen::CanBus;
+                    }
+                    event::KeyCode::Tab => {
+                        app.select_next_compare_bus();
+                    }
+                    event::KeyCode::Down => {
+                        app.select_next_comparison();
+                    }
+                    event::KeyCode::Up => {
+                        app.select_previous_comparison();
+                    }
+                    _ => {}
+                },
+                app::ActiveScreen::Ecus => match key.code {
+                    event::KeyCode::Char('q') => {
+                        return Ok(true);
+                    }
+                    event::KeyCode::Char('u') | event::KeyCode::Esc => {
+                        app.active_screen = app::ActiveScreen::CanBus;
+                    }
+                    event::KeyCode::Down => {
+                        app.select_next_ecu();
+                    }
+                    event::KeyCode::Up => {
+                        app.select_previous_ecu();
+                    }
+                    _ => {}
+                },
+                app::ActiveScreen::Translations => match key.code {
+                    event::KeyCode::Char('q') => {
+                        return Ok(true);
+                    }
+                    event::KeyCode::Char('t') | event::KeyCode::Esc => {
+                        app.active_screen = app::ActiveScreen::CanBus;
+                    }
+                    event::KeyCode::Char('l') => {
+                        app.cycle_language();
+                        app.selected_missing = 0;
+                    }
+                    event::KeyCode::Down => {
+                        app.select_next_missing();
+                    }
+                    event::KeyCode::Up => {
+                        app.select_previous_missing();
+                    }
+                    event::KeyCode::Enter => {
+                        app.input.clear();
+                        app.status = None;
+                        app.edit_window = Some(app::EditWindow::Translation);
+                        app.active_screen = app::ActiveScreen::Editing;
                     }
                     _ => {}
                 },
-                _ => {}
             }
         }
     }
@@ -1,6 +1,11 @@
 use ratatui::{Frame, layout, style, text, widgets};
 
 use crate::app;
+use crate::bus::can;
+use crate::bus::can::coverage;
+use crate::bus::can::diagnostic::Severity;
+use crate::bus::can::ecu;
+use std::path::Path;
 
 pub fn ui(frame: &mut Frame, app: &app::App) {
     let chunks = layout::Layout::default()
@@ -22,72 +27,573 @@ pub fn ui(frame: &mut Frame, app: &app::App) {
     ))
     .block(title_block);
 
-    let work_dir_block = widgets::Block::default()
-        .borders(widgets::Borders::ALL)
-        .style(style::Style::default());
-
-    let work_dir = widgets::Paragraph::new(text::Text::styled(
-        app.app_config.database_dir.clone(),
-        style::Style::default().fg(style::Color::Cyan),
-    ))
-    .block(work_dir_block);
+    let bus_tabs = widgets::Tabs::new(app.buses.iter().map(|bus| bus.name()))
+        .select(app.active_bus)
+        .style(style::Style::default().fg(style::Color::Cyan))
+        .highlight_style(
+            style::Style::default()
+                .fg(style::Color::Yellow)
+                .bg(style::Color::DarkGray),
+        )
+        .block(
+            widgets::Block::default()
+                .title(app.app_config.database_dir.clone())
+                .borders(widgets::Borders::ALL),
+        );
 
     let header_chunks = layout::Layout::default()
         .direction(layout::Direction::Horizontal)
         .constraints([layout::Constraint::Length(10), layout::Constraint::Min(80)])
         .split(chunks[0]);
     frame.render_widget(title, header_chunks[0]);
-    frame.render_widget(work_dir, header_chunks[1]);
+    frame.render_widget(bus_tabs, header_chunks[1]);
 
     // Center chunk
+    match (&app.active_screen, &app.edit_window) {
+        (app::ActiveScreen::Problems, _) => render_problems(frame, app, chunks[1]),
+        (app::ActiveScreen::Compare, _) => render_compare(frame, app, chunks[1]),
+        (app::ActiveScreen::Ecus, _) => render_ecus(frame, app, chunks[1]),
+        (app::ActiveScreen::Translations, _) | (_, Some(app::EditWindow::Translation)) => {
+            render_translations(frame, app, chunks[1])
+        }
+        _ => render_can_messages(frame, app, chunks[1]),
+    }
+
+    let current_keys_hint = {
+        match app.active_screen {
+            app::ActiveScreen::CanBus => text::Span::styled(
+                format!(
+                    "Quit[q] Nav[↑↓] Bus[Tab] Compare[c] ECUs[u] New[n] Edit[e] Problems[p]({}) Lang[l]({})",
+                    app.diagnostics.len(),
+                    app.app_config.default_lang
+                ),
+                style::Style::default().fg(style::Color::Green),
+            ),
+            app::ActiveScreen::Editing => match app.edit_window {
+                Some(app::EditWindow::Translation) => text::Span::styled(
+                    "Save[Enter] Cancel[Esc]",
+                    style::Style::default().fg(style::Color::Green),
+                ),
+                _ => text::Span::styled(
+                    "Quit[q] Nav[↑↓] Select[s]",
+                    style::Style::default().fg(style::Color::Green),
+                ),
+            },
+            app::ActiveScreen::Problems => text::Span::styled(
+                format!(
+                    "Quit[q] Back[p] - {} file(s) failed to load",
+                    app.failed_file_count()
+                ),
+                style::Style::default().fg(style::Color::Green),
+            ),
+            app::ActiveScreen::Compare => text::Span::styled(
+                "Quit[q] Back[c] Nav[↑↓] Other bus[Tab]",
+                style::Style::default().fg(style::Color::Green),
+            ),
+            app::ActiveScreen::Ecus => text::Span::styled(
+                "Quit[q] Back[u] Nav[↑↓]",
+                style::Style::default().fg(style::Color::Green),
+            ),
+            app::ActiveScreen::Translations => text::Span::styled(
+                format!(
+                    "Quit[q] Back[t] Nav[↑↓] Translate[Enter] Lang[l]({})",
+                    app.app_config.default_lang
+                ),
+                style::Style::default().fg(style::Color::Green),
+            ),
+        }
+    };
+
+    let mut footer_spans = vec![current_keys_hint];
+    if let Some(status) = &app.status {
+        footer_spans.push(text::Span::styled(
+            format!("  {}", status),
+            style::Style::default().fg(style::Color::Cyan),
+        ));
+    }
+
+    let key_notes_footer = widgets::Paragraph::new(text::Line::from(footer_spans))
+        .block(widgets::Block::default().borders(widgets::Borders::ALL));
+
+    frame.render_widget(key_notes_footer, chunks[2]);
+
+    match app.edit_window {
+        Some(app::EditWindow::Translation) => render_translation_popup(frame, app),
+        Some(_) => {
+            let popup_block = widgets::Block::default()
+                .title("Edit window")
+                .borders(widgets::Borders::NONE)
+                .style(style::Style::default().bg(style::Color::DarkGray));
+
+            let area = centered_rect(80, 60, frame.area());
+            frame.render_widget(popup_block, area);
+        }
+        None => {}
+    }
+}
+
+fn render_can_messages(frame: &mut Frame, app: &app::App, area: layout::Rect) {
+    let center_chunks = layout::Layout::default()
+        .direction(layout::Direction::Horizontal)
+        .constraints([layout::Constraint::Length(64), layout::Constraint::Min(20)])
+        .split(area);
+
     let mut list_items = Vec::<widgets::ListItem>::new();
 
-    for item in app.can_messages.clone() {
+    for item in app.database().messages() {
         list_items.push(widgets::ListItem::new(text::Line::from(
             text::Span::styled(
                 format!(
-                    "{: <10}|{: <30}|{: >4}|{: >2}",
-                    item.id.unwrap(),
-                    item.name.unwrap().chars().take(30).collect::<String>(),
-                    item.periodicity.unwrap(),
-                    item.length.unwrap(),
+                    "{: <10}|{: <30}|{: >12}|{: >2}",
+                    item.id.map_or(String::from("?"), |id| id.to_string()),
+                    item.name
+                        .as_deref()
+                        .unwrap_or("")
+                        .chars()
+                        .take(30)
+                        .collect::<String>(),
+                    item.periodicity
+                        .as_ref()
+                        .map_or(String::new(), |p| p.to_string()),
+                    item.length.map_or(String::new(), |l| l.to_string()),
                 ),
                 style::Style::default().fg(style::Color::Yellow),
             ),
         )));
     }
 
-    let list = widgets::List::new(list_items);
+    let list = widgets::List::new(list_items)
+        .highlight_style(style::Style::default().bg(style::Color::DarkGray));
+    let mut list_state = widgets::ListState::default().with_selected(Some(app.selected_message));
 
-    frame.render_widget(list, chunks[1]);
+    frame.render_stateful_widget(list, center_chunks[0], &mut list_state);
 
-    let current_keys_hint = {
-        match app.active_screen {
-            app::ActiveScreen::CanBus => text::Span::styled(
-                "Quit[q] Nav[↑↓] New[n] Edit[e]",
-                style::Style::default().fg(style::Color::Green),
+    let details = match app.selected_can_message() {
+        Some(message) => message_details(message, &app.app_config.language_chain()),
+        None => text::Text::from("No CAN message loaded."),
+    };
+    let details_panel = widgets::Paragraph::new(details)
+        .wrap(widgets::Wrap { trim: false })
+        .block(
+            widgets::Block::default()
+                .title(format!("Details [{}]", app.app_config.default_lang))
+                .borders(widgets::Borders::ALL),
+        );
+
+    frame.render_widget(details_panel, center_chunks[1]);
+}
+
+/// Message header, comment and signals, with texts resolved in `langs`.
+fn message_details<'a>(message: &'a can::CanMessage, langs: &[&str]) -> text::Text<'a> {
+    let label_style = style::Style::default().fg(style::Color::Cyan);
+    let comment_style = style::Style::default().fg(style::Color::Gray);
+    let mut lines = Vec::<text::Line>::new();
+
+    lines.push(text::Line::from(vec![
+        text::Span::styled(
+            message.name.clone().unwrap_or_default(),
+            style::Style::default().fg(style::Color::Yellow),
+        ),
+        text::Span::raw(format!(
+            "  {}  {} bytes  {}",
+            message.id.map_or(String::from("?"), |id| id.to_string()),
+            message.length.map_or(String::from("?"), |l| l.to_string()),
+            message
+                .periodicity
+                .as_ref()
+                .map_or(String::new(), |p| p.to_psa_re()),
+        )),
+    ]));
+    if let Some(comment) = message.comment.as_ref().and_then(|c| c.resolve(langs)) {
+        lines.push(text::Line::styled(comment.to_string(), comment_style));
+    }
+    lines.push(text::Line::from(vec![
+        text::Span::styled("Senders: ", label_style),
+        text::Span::raw(message.senders.join(", ")),
+    ]));
+    lines.push(text::Line::from(vec![
+        text::Span::styled("Receivers: ", label_style),
+        text::Span::raw(message.receivers.join(", ")),
+    ]));
+
+    for (name, signal) in &message.signals {
+        lines.push(text::Line::raw(""));
+        let bits = match &signal.bits {
+            Some(Ok(range)) => range.to_string(),
+            Some(Err(err)) => format!("{} (invalid)", err.text()),
+            None => String::from("?"),
+        };
+        let mut header = format!("  bits {}", bits);
+        if let Some(data_type) = &signal.data_type {
+            header.push_str(&format!("  {}", data_type));
+        }
+        if signal.factor.is_some() || signal.offset.is_some() {
+            header.push_str(&format!(
+                "  x{} {:+}",
+                signal.factor.unwrap_or(1.0),
+                signal.offset.unwrap_or(0.0)
+            ));
+        }
+        if let Some(units) = &signal.units {
+            header.push_str(&format!("  [{}]", units));
+        }
+        lines.push(text::Line::from(vec![
+            text::Span::styled(name.clone(), label_style),
+            text::Span::raw(header),
+        ]));
+        if let Some(comment) = signal.comment.as_ref().and_then(|c| c.resolve(langs)) {
+            lines.push(text::Line::styled(format!("  {}", comment), comment_style));
+        }
+        for (value, label) in &signal.values {
+            let label = label.as_ref().and_then(|l| l.resolve(langs)).unwrap_or("");
+            lines.push(text::Line::raw(format!("    {: >4}: {}", value, label)));
+        }
+    }
+
+    text::Text::from(lines)
+}
+
+fn render_problems(frame: &mut Frame, app: &app::App, area: layout::Rect) {
+    let mut list_items = Vec::<widgets::ListItem>::new();
+
+    for diagnostic in &app.diagnostics {
+        let color = match diagnostic.severity {
+            Severity::Error => style::Color::Red,
+            Severity::Warning => style::Color::Yellow,
+        };
+        list_items.push(widgets::ListItem::new(text::Line::from(
+            text::Span::styled(diagnostic.to_string(), style::Style::default().fg(color)),
+        )));
+    }
+
+    let list = widgets::List::new(list_items).block(
+        widgets::Block::default()
+            .title(format!("Problems ({})", app.diagnostics.len()))
+            .borders(widgets::Borders::ALL),
+    );
+
+    frame.render_widget(list, area);
+}
+
+fn render_compare(frame: &mut Frame, app: &app::App, area: layout::Rect) {
+    let center_chunks = layout::Layout::default()
+        .direction(layout::Direction::Horizontal)
+        .constraints([layout::Constraint::Length(64), layout::Constraint::Min(20)])
+        .split(area);
+    let bus_name = |index: usize| app.buses.get(index).map_or(String::new(), |bus| bus.name());
+
+    let comparisons = app.comparisons();
+    let items: Vec<widgets::ListItem> = comparisons
+        .iter()
+        .map(|comparison| {
+            let (marker, color) = match (comparison.left, comparison.right) {
+                (Some(_), None) => ("<", style::Color::Red),
+                (None, Some(_)) => (">", style::Color::Red),
+                _ if comparison.is_identical() => ("=", style::Color::Green),
+                _ => ("≠", style::Color::Yellow),
+            };
+            let label = comparison
+                .left
+                .or(comparison.right)
+                .and_then(|message| message.name.clone())
+                .unwrap_or_default();
+            let id = |message: Option<&can::CanMessage>| {
+                message
+                    .and_then(|message| message.id)
+                    .map_or(String::from("-"), |id| id.to_string())
+            };
+            widgets::ListItem::new(text::Line::styled(
+                format!(
+                    "{} {: <30} {: >10} {: >10}",
+                    marker,
+                    label.chars().take(30).collect::<String>(),
+                    id(comparison.left),
+                    id(comparison.right)
+                ),
+                style::Style::default().fg(color),
+            ))
+        })
+        .collect();
+    let identical = comparisons.iter().filter(|c| c.is_identical()).count();
+    let list = widgets::List::new(items)
+        .highlight_style(style::Style::default().bg(style::Color::DarkGray))
+        .block(
+            widgets::Block::default()
+                .title(format!(
+                    "{} vs {} ({}/{} identical)",
+                    bus_name(app.active_bus),
+                    bus_name(app.compare_bus),
+                    identical,
+                    comparisons.len()
+                ))
+                .borders(widgets::Borders::ALL),
+        );
+    let mut list_state = widgets::ListState::default().with_selected(Some(app.selected_comparison));
+    frame.render_stateful_widget(list, center_chunks[0], &mut list_state);
+
+    let label_style = style::Style::default().fg(style::Color::Cyan);
+    let mut lines = Vec::<text::Line>::new();
+    if let Some(comparison) = comparisons.get(app.selected_comparison) {
+        lines.push(text::Line::from(vec![
+            text::Span::styled(format!("{}: ", bus_name(app.active_bus)), label_style),
+            text::Span::raw(
+                comparison
+                    .left
+                    .map_or(String::from("-"), can::CanMessage::label),
             ),
-            app::ActiveScreen::Editing => text::Span::styled(
-                "Quit[q] Nav[↑↓] Select[s]",
-                style::Style::default().fg(style::Color::Green),
+        ]));
+        lines.push(text::Line::from(vec![
+            text::Span::styled(format!("{}: ", bus_name(app.compare_bus)), label_style),
+            text::Span::raw(
+                comparison
+                    .right
+                    .map_or(String::from("-"), can::CanMessage::label),
             ),
+        ]));
+        lines.push(text::Line::raw(""));
+        if comparison.is_identical() {
+            lines.push(text::Line::raw("No difference."));
         }
-    };
+        for difference in &comparison.differences {
+            lines.push(text::Line::from(vec![
+                text::Span::styled(difference.field.clone(), label_style),
+                text::Span::raw(format!(
+                    "  {} → {}",
+                    difference.left.as_deref().unwrap_or("-"),
+                    difference.right.as_deref().unwrap_or("-")
+                )),
+            ]));
+        }
+    }
+    let details = widgets::Paragraph::new(text::Text::from(lines))
+        .wrap(widgets::Wrap { trim: false })
+        .block(
+            widgets::Block::default()
+                .title("Differences")
+                .borders(widgets::Borders::ALL),
+        );
+    frame.render_widget(details, center_chunks[1]);
+}
 
-    let key_notes_footer = widgets::Paragraph::new(text::Line::from(current_keys_hint))
-        .block(widgets::Block::default().borders(widgets::Borders::ALL));
+fn render_ecus(frame: &mut Frame, app: &app::App, area: layout::Rect) {
+    let center_chunks = layout::Layout::default()
+        .direction(layout::Direction::Horizontal)
+        .constraints([layout::Constraint::Length(40), layout::Constraint::Min(20)])
+        .split(area);
+    let messages = app.database().messages();
+    let registry = app.ecu_registry();
+    let unsent = ecu::unsent_messages(messages);
 
-    frame.render_widget(key_notes_footer, chunks[2]);
+    let items: Vec<widgets::ListItem> = registry
+        .ecus()
+        .iter()
+        .map(|ecu| {
+            let color = if ecu.file_path.is_some() {
+                style::Color::Yellow
+            } else {
+                style::Color::Gray
+            };
+            widgets::ListItem::new(text::Line::styled(
+                format!(
+                    "{: <20} tx {: >3} rx {: >3}",
+                    ecu.name.chars().take(20).collect::<String>(),
+                    ecu.transmits.len(),
+                    ecu.receives.len()
+                ),
+                style::Style::default().fg(color),
+            ))
+        })
+        .collect();
+    let list = widgets::List::new(items)
+        .highlight_style(style::Style::default().bg(style::Color::DarkGray))
+        .block(
+            widgets::Block::default()
+                .title(format!("ECUs ({})", registry.ecus().len()))
+                .borders(widgets::Borders::ALL),
+        );
+    let mut list_state = widgets::ListState::default().with_selected(Some(app.selected_ecu));
+    frame.render_stateful_widget(list, center_chunks[0], &mut list_state);
 
-    if let Some(editing) = &app.edit_window {
-        let popup_block = widgets::Block::default()
-            .title("Edit window")
-            .borders(widgets::Borders::NONE)
-            .style(style::Style::default().bg(style::Color::DarkGray));
+    let right_chunks = layout::Layout::default()
+        .direction(layout::Direction::Vertical)
+        .constraints([
+            layout::Constraint::Min(5),
+            layout::Constraint::Length(unsent.len().clamp(1, 8) as u16 + 2),
+        ])
+        .split(center_chunks[1]);
 
-        let area = centered_rect(80, 60, frame.area());
-        frame.render_widget(popup_block, area);
+    let label_style = style::Style::default().fg(style::Color::Cyan);
+    let mut lines = Vec::<text::Line>::new();
+    if let Some(ecu) = registry.ecus().get(app.selected_ecu) {
+        lines.push(text::Line::styled(
+            ecu.name.clone(),
+            style::Style::default().fg(style::Color::Yellow),
+        ));
+        if !ecu.alt_names.is_empty() {
+            lines.push(text::Line::from(vec![
+                text::Span::styled("Alt names: ", label_style),
+                text::Span::raw(ecu.alt_names.join(", ")),
+            ]));
+        }
+        if let Some(comment) = ecu
+            .comment
+            .as_ref()
+            .and_then(|c| c.resolve(&app.app_config.language_chain()))
+        {
+            lines.push(text::Line::styled(
+                comment.to_string(),
+                style::Style::default().fg(style::Color::Gray),
+            ));
+        }
+        for (title, indexes) in [("Transmits", &ecu.transmits), ("Receives", &ecu.receives)] {
+            lines.push(text::Line::raw(""));
+            lines.push(text::Line::styled(
+                format!("{} ({})", title, indexes.len()),
+                label_style,
+            ));
+            for &index in indexes {
+                lines.push(text::Line::raw(format!(
+                    "  {}",
+                    messages
+                        .get(index)
+                        .map_or(String::from("-"), can::CanMessage::label)
+                )));
+            }
+        }
     }
+    let details = widgets::Paragraph::new(text::Text::from(lines))
+        .wrap(widgets::Wrap { trim: false })
+        .block(
+            widgets::Block::default()
+                .title("ECU")
+                .borders(widgets::Borders::ALL),
+        );
+    frame.render_widget(details, right_chunks[0]);
+
+    let unsent_items: Vec<widgets::ListItem> = unsent
+        .iter()
+        .map(|&index| {
+            widgets::ListItem::new(text::Line::styled(
+                messages
+                    .get(index)
+                    .map_or(String::from("-"), can::CanMessage::label),
+                style::Style::default().fg(style::Color::Red),
+            ))
+        })
+        .collect();
+    let unsent_list = widgets::List::new(unsent_items).block(
+        widgets::Block::default()
+            .title(format!("Messages without sender ({})", unsent.len()))
+            .borders(widgets::Borders::ALL),
+    );
+    frame.render_widget(unsent_list, right_chunks[1]);
+}
+
+fn render_translations(frame: &mut Frame, app: &app::App, area: layout::Rect) {
+    let lang = &app.app_config.default_lang;
+    let center_chunks = layout::Layout::default()
+        .direction(layout::Direction::Horizontal)
+        .constraints([layout::Constraint::Length(40), layout::Constraint::Min(20)])
+        .split(area);
+
+    let files = coverage::coverage(app.database().messages(), lang);
+    let (translated, total) = files
+        .iter()
+        .fold((0, 0), |(t, n), file| (t + file.translated, n + file.total));
+    let coverage_items: Vec<widgets::ListItem> = files
+        .iter()
+        .map(|file| {
+            let color = if file.translated == file.total {
+                style::Color::Green
+            } else {
+                style::Color::Yellow
+            };
+            let file_name = Path::new(&file.file_path)
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().to_string());
+            widgets::ListItem::new(text::Line::styled(
+                format!(
+                    "{: >5.1}% {: >3}/{: <3} {}",
+                    file.percent(),
+                    file.translated,
+                    file.total,
+                    file_name
+                ),
+                style::Style::default().fg(color),
+            ))
+        })
+        .collect();
+    let coverage_list = widgets::List::new(coverage_items).block(
+        widgets::Block::default()
+            .title(format!("Coverage [{}] {}/{}", lang, translated, total))
+            .borders(widgets::Borders::ALL),
+    );
+    frame.render_widget(coverage_list, center_chunks[0]);
+
+    let missing = app.missing_translations();
+    let missing_items: Vec<widgets::ListItem> = missing
+        .iter()
+        .map(|item| {
+            let message = &app.database().messages()[item.message_index];
+            widgets::ListItem::new(text::Line::from(vec![
+                text::Span::styled(
+                    format!(
+                        "{} {}",
+                        message.id.map_or(String::from("?"), |id| id.to_string()),
+                        item.location.field_path()
+                    ),
+                    style::Style::default().fg(style::Color::Yellow),
+                ),
+                text::Span::raw(format!("  {}", item.reference.as_deref().unwrap_or(""))),
+            ]))
+        })
+        .collect();
+    let missing_list = widgets::List::new(missing_items)
+        .highlight_style(style::Style::default().bg(style::Color::DarkGray))
+        .block(
+            widgets::Block::default()
+                .title(format!("Missing [{}] ({})", lang, missing.len()))
+                .borders(widgets::Borders::ALL),
+        );
+    let mut list_state = widgets::ListState::default().with_selected(Some(app.selected_missing));
+    frame.render_stateful_widget(missing_list, center_chunks[1], &mut list_state);
+}
+
+fn render_translation_popup(frame: &mut Frame, app: &app::App) {
+    let missing = app.missing_translations();
+    let Some(item) = missing.get(app.selected_missing) else {
+        return;
+    };
+    let label_style = style::Style::default().fg(style::Color::Cyan);
+
+    let lines = vec![
+        text::Line::from(vec![
+            text::Span::styled("Field: ", label_style),
+            text::Span::raw(item.location.field_path()),
+        ]),
+        text::Line::from(vec![
+            text::Span::styled("Reference: ", label_style),
+            text::Span::raw(item.reference.clone().unwrap_or_default()),
+        ]),
+        text::Line::raw(""),
+        text::Line::from(vec![
+            text::Span::styled(format!("{}: ", app.app_config.default_lang), label_style),
+            text::Span::raw(format!("{}_", app.input)),
+        ]),
+    ];
+
+    let popup = widgets::Paragraph::new(lines)
+        .wrap(widgets::Wrap { trim: false })
+        .block(
+            widgets::Block::default()
+                .title("Translate")
+                .borders(widgets::Borders::ALL)
+                .style(style::Style::default().bg(style::Color::DarkGray)),
+        );
+
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(widgets::Clear, area);
+    frame.render_widget(popup, area);
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: layout::Rect) -> layout::Rect {